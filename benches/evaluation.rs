@@ -0,0 +1,62 @@
+extern crate tis_100_superoptimizer;
+
+use std::time::{Duration, Instant};
+
+use tis_100_superoptimizer::TIS_100::{Node, Program, Instruction, Source, Destination, Register, Cycle};
+use tis_100_superoptimizer::TIS_100::Ports::{Port, Direction};
+use tis_100_superoptimizer::TIS_100::Executors::Executor;
+use tis_100_superoptimizer::TIS_100::Bytecodes::{Bytecode, Evaluator};
+
+const ITERATIONS: u32 = 2_000;
+const MAXIMUM_CYCLE: u32 = 1_000;
+
+fn measure<F>(name: &str, mut run: F) -> Duration where F: FnMut() -> usize {
+    let start = Instant::now();
+    let mut written = 0;
+    for _ in 0..ITERATIONS {
+        written += run();
+    }
+    let elapsed = start.elapsed();
+    println!("{:<24} {:>10.2?} per run ({} values written)", name, elapsed / ITERATIONS, written / ITERATIONS as usize);
+    elapsed
+}
+
+fn main() {
+    let input: Vec<i32> = (0..39).map(|value| (value * 37) % 199 - 99).collect();
+    let node: Node = Node::new().set_up(Port::new(input.clone()));
+    let program: Program = Program(vec![
+        Instruction::LABEL(String::from("START")),
+        Instruction::MOV(Source::Port(Direction::UP), Destination::Register(Register::ACC)),
+        Instruction::JGZ(String::from("POSITIVE")),
+        Instruction::NEG,
+        Instruction::LABEL(String::from("POSITIVE")),
+        Instruction::SAV,
+        Instruction::ADD(Source::Register(Register::ACC)),
+        Instruction::SUB(Source::Literal(1)),
+        Instruction::MOV(Source::Register(Register::ACC), Destination::Port(Direction::DOWN)),
+        Instruction::JMP(String::from("START")),
+    ]);
+    let loaded: Node = node.load(program.clone());
+    let Program(ref instructions) = program;
+
+    let node_run = measure("Node::run", || {
+        loaded.run(Cycle::Maximum(MAXIMUM_CYCLE)).ok().map_or(0, |result| result.down.output.len())
+    });
+    let mut buffer = vec![0; input.len()];
+    let executor_run = measure("Executor::run", || {
+        let mut executor = Executor::new(&node, instructions, &mut buffer);
+        executor.run(Cycle::Maximum(MAXIMUM_CYCLE), |_| true);
+        executor.output().len()
+    });
+    let compile_once = Bytecode::compile(&program);
+    let evaluator_run = measure("Evaluator::run", || {
+        let mut evaluator = Evaluator::new(&node, &compile_once, &mut buffer);
+        evaluator.run(Cycle::Maximum(MAXIMUM_CYCLE), |_| true);
+        evaluator.output().len()
+    });
+    measure("Bytecode::compile", || Bytecode::compile(&program).ops.len());
+
+    println!("Executor is {:.1}x and Evaluator {:.1}x as fast as Node::run",
+             node_run.as_secs_f64() / executor_run.as_secs_f64(),
+             node_run.as_secs_f64() / evaluator_run.as_secs_f64());
+}
@@ -0,0 +1,409 @@
+//! `Bytecode` is a `Program` compiled for fast evaluation.
+//!
+//! Compiling resolves what the interpreters decide again for every executed
+//! instruction: labels become positions, operands are decoded and literals
+//! saturated, and moves and arithmetic with constant operands are folded into
+//! simpler operations. Labels themselves take no cycles and are dropped.
+//!
+//! An `Evaluator` runs `Bytecode` in place, like an `Executors::Executor`
+//! runs instructions, and behaves like `Node::run`. Like `Node::run`, it
+//! refuses to run `Bytecode` with a jump to an unknown label.
+
+use super::{Node, Program, Instruction, Source, Destination, Register, Cycle, saturate, unknown_label};
+use super::Ports::Direction;
+use super::Executors::Halt;
+use super::Streams::Streams;
+
+/// Where an `Op` takes a value from
+#[derive(Debug,PartialEq,Eq,Clone,Copy)]
+pub enum Operand {
+    /// A constant, already saturated
+    Constant(i32),
+    /// The accumulator
+    Acc,
+    /// The port in a certain `Direction`
+    Port(Direction),
+}
+
+/// Where an `Op` puts a value
+#[derive(Debug,PartialEq,Eq,Clone,Copy)]
+pub enum Target {
+    /// The value is discarded
+    Nil,
+    /// The accumulator
+    Acc,
+    /// The port in a certain `Direction`
+    Port(Direction),
+}
+
+/// When a jump is taken
+#[derive(Debug,PartialEq,Eq,Clone,Copy)]
+pub enum Condition {
+    /// Always
+    Always,
+    /// When the accumulator equals zero
+    Zero,
+    /// When the accumulator does not equal zero
+    NonZero,
+    /// When the accumulator is greater than zero
+    Positive,
+    /// When the accumulator is less than zero
+    Negative,
+}
+
+/// A single operation of `Bytecode`
+#[derive(Debug,PartialEq,Eq,Clone,Copy)]
+pub enum Op {
+    /// Does nothing
+    Nop,
+    /// Sets the accumulator to a constant
+    SetAcc(i32),
+    /// Moves a value
+    Mov(Operand, Target),
+    /// Swaps the accumulator and the backup register
+    Swp,
+    /// Saves the accumulator to the backup register
+    Sav,
+    /// Adds a value to the accumulator
+    Add(Operand),
+    /// Subtracts a value from the accumulator
+    Sub(Operand),
+    /// Negates the accumulator
+    Neg,
+    /// Jumps to a position when the `Condition` holds. A jump to an unknown
+    /// label has no position, and an `Evaluator` refuses to run its `Bytecode`
+    Jump(Condition, Option<usize>),
+    /// Jumps relative to this operation
+    Jro(Operand),
+}
+
+impl Op {
+    /// The number of cycles this operation takes, like `Instruction::cycles`
+    pub fn cycles(&self) -> u32 {
+        match *self {
+            Op::Mov(_, Target::Port(_)) => 2,
+            _ => 1,
+        }
+    }
+}
+
+/// `Bytecode` holds the compiled operations of a `Program`
+#[derive(Debug,PartialEq,Eq,Clone)]
+pub struct Bytecode {
+    /// The operations, one for every instruction that is not a `LABEL`
+    pub ops: Vec<Op>,
+    /// The first label a jump targets that the `Program` does not define
+    pub unknown_label: Option<String>,
+    /// The position of the operation of every instruction of the `Program`.
+    /// A `LABEL` has the position of the operation that follows it.
+    pub positions: Vec<usize>,
+}
+
+impl Bytecode {
+    /// Compile `program` into `Bytecode`
+    pub fn compile(program: &Program) -> Bytecode {
+        let Program(ref instructions) = *program;
+        let positions: Vec<usize> = instructions.iter()
+            .scan(0, |position, instruction| {
+                let current = *position;
+                if !is_label(instruction) {
+                    *position += 1;
+                }
+                Some(current)
+            })
+            .collect();
+        let position_of = |label: &str| instructions.iter()
+            .position(|instruction| matches!(*instruction, Instruction::LABEL(ref name) if name == label))
+            .map(|index| positions[index]);
+
+        let ops = instructions.iter()
+            .filter(|instruction| !is_label(instruction))
+            .map(|instruction| match *instruction {
+                Instruction::NOP | Instruction::LABEL(_) => Op::Nop,
+                Instruction::MOV(ref source, ref destination) => match (operand(source), target(destination)) {
+                    (Operand::Constant(value), Target::Acc) => Op::SetAcc(value),
+                    (Operand::Acc, Target::Acc) | (Operand::Constant(_), Target::Nil) | (Operand::Acc, Target::Nil) => Op::Nop,
+                    (from, to) => Op::Mov(from, to),
+                },
+                Instruction::SWP => Op::Swp,
+                Instruction::SAV => Op::Sav,
+                Instruction::ADD(ref source) => match operand(source) {
+                    Operand::Constant(0) => Op::Nop,
+                    from => Op::Add(from),
+                },
+                Instruction::SUB(ref source) => match operand(source) {
+                    Operand::Constant(0) => Op::Nop,
+                    Operand::Acc => Op::SetAcc(0),
+                    from => Op::Sub(from),
+                },
+                Instruction::NEG => Op::Neg,
+                Instruction::JMP(ref label) => Op::Jump(Condition::Always, position_of(label)),
+                Instruction::JEZ(ref label) => Op::Jump(Condition::Zero, position_of(label)),
+                Instruction::JNZ(ref label) => Op::Jump(Condition::NonZero, position_of(label)),
+                Instruction::JGZ(ref label) => Op::Jump(Condition::Positive, position_of(label)),
+                Instruction::JLZ(ref label) => Op::Jump(Condition::Negative, position_of(label)),
+                Instruction::JRO(ref source) => Op::Jro(operand(source)),
+            })
+            .collect();
+        Bytecode { ops, unknown_label: unknown_label(instructions).cloned(), positions }
+    }
+}
+
+fn is_label(instruction: &Instruction) -> bool {
+    matches!(*instruction, Instruction::LABEL(_))
+}
+
+fn operand(source: &Source) -> Operand {
+    match *source {
+        Source::Port(direction) => Operand::Port(direction),
+        Source::Register(Register::NIL) => Operand::Constant(0),
+        Source::Register(Register::ACC) => Operand::Acc,
+        Source::Literal(value) => Operand::Constant(saturate(value)),
+    }
+}
+
+fn target(destination: &Destination) -> Target {
+    match *destination {
+        Destination::Port(direction) => Target::Port(direction),
+        Destination::Register(Register::NIL) => Target::Nil,
+        Destination::Register(Register::ACC) => Target::Acc,
+    }
+}
+
+/// An `Evaluator` runs `Bytecode` on borrowed inputs and a borrowed output.
+/// Only values written to the down port are recorded.
+pub struct Evaluator<'a> {
+    ops: &'a [Op],
+    unknown_label: bool,
+    streams: Streams<'a>,
+    /// The accumulator
+    pub acc: i32,
+    /// The backup register
+    pub bac: i32,
+    /// The number of cycles the evaluator has run
+    pub cycles: u32,
+    pc: usize,
+}
+
+impl<'a> Evaluator<'a> {
+    /// Create an `Evaluator` that starts in the state of `node`, at the
+    /// operation of its program counter, reading the input of its ports,
+    /// running `bytecode` and writing the values for the down port into
+    /// `output`
+    pub fn new(node: &'a Node, bytecode: &'a Bytecode, output: &'a mut [i32]) -> Evaluator<'a> {
+        Evaluator {
+            ops: &bytecode.ops,
+            unknown_label: bytecode.unknown_label.is_some(),
+            streams: Streams::new(node, output),
+            acc: node.acc,
+            bac: node.bac,
+            cycles: node.cycles,
+            pc: bytecode.positions.get(node.pc).cloned().unwrap_or(bytecode.ops.len()),
+        }
+    }
+
+    /// The values written to the down port so far
+    pub fn output(&self) -> &[i32] {
+        self.streams.output()
+    }
+
+    /// Run the bytecode like `Node::run_while`, stopping as soon as `proceed`
+    /// returns false after an executed operation
+    pub fn run<F>(&mut self, allowed_cycles: Cycle, mut proceed: F) -> Halt where F: FnMut(&Evaluator) -> bool {
+        if self.unknown_label {
+            return Halt::UnknownLabel;
+        }
+        if self.ops.is_empty() {
+            return Halt::Finished;
+        }
+        let ops = self.ops;
+
+        loop {
+            match ops.get(self.pc) {
+                Some(&op) => {
+                    if let Err(halt) = self.execute(op) {
+                        return halt;
+                    }
+                    self.cycles += op.cycles();
+                    if !proceed(self) {
+                        return Halt::Stopped;
+                    }
+                    if !allowed_cycles.should_continu(self.cycles) {
+                        return Halt::Timeout;
+                    }
+                },
+                None => {
+                    if self.streams.input_available() {
+                        self.pc = 0;
+                    } else {
+                        return Halt::Finished;
+                    }
+                },
+            }
+        }
+    }
+
+    /// Execute `op`, returning why the run halts if it can not be executed
+    fn execute(&mut self, op: Op) -> Result<(), Halt> {
+        match op {
+            Op::Nop => (),
+            Op::SetAcc(value) => self.acc = value,
+            Op::Mov(from, to) => {
+                let value = self.value_from(from).ok_or(Halt::Finished)?;
+                match to {
+                    Target::Nil => (),
+                    Target::Acc => self.acc = value,
+                    Target::Port(direction) => self.streams.write_to(direction, value)?,
+                }
+            },
+            Op::Swp => std::mem::swap(&mut self.acc, &mut self.bac),
+            Op::Sav => self.bac = self.acc,
+            Op::Add(from) => {
+                let value = self.value_from(from).ok_or(Halt::Finished)?;
+                self.acc = saturate(self.acc.saturating_add(value));
+            },
+            Op::Sub(from) => {
+                let value = self.value_from(from).ok_or(Halt::Finished)?;
+                self.acc = saturate(self.acc.saturating_sub(value));
+            },
+            Op::Neg => self.acc = -self.acc,
+            Op::Jump(condition, position) => {
+                let taken = match condition {
+                    Condition::Always => true,
+                    Condition::Zero => self.acc == 0,
+                    Condition::NonZero => self.acc != 0,
+                    Condition::Positive => self.acc > 0,
+                    Condition::Negative => self.acc < 0,
+                };
+                if taken {
+                    self.pc = position.ok_or(Halt::UnknownLabel)?;
+                    return Ok(());
+                }
+            },
+            Op::Jro(from) => {
+                let offset = self.value_from(from).ok_or(Halt::Finished)?;
+                let last = (self.ops.len() - 1) as i64;
+                self.pc = (self.pc as i64 + offset as i64).clamp(0, last) as usize;
+                return Ok(());
+            },
+        }
+        self.pc += 1;
+        Ok(())
+    }
+
+    fn value_from(&mut self, operand: Operand) -> Option<i32> {
+        match operand {
+            Operand::Constant(value) => Some(value),
+            Operand::Acc => Some(self.acc),
+            Operand::Port(direction) => self.streams.read_from(direction),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Node, Program, Instruction, Source, Destination, Register, Cycle};
+    use super::super::Ports::{Port, Direction};
+    use super::super::Executors::Executor;
+    use super::*;
+
+    fn evaluate(node: &Node, program: &Program) -> (Halt, Vec<i32>, u32) {
+        let bytecode = Bytecode::compile(program);
+        let mut buffer = vec![0; 20];
+        let mut evaluator = Evaluator::new(node, &bytecode, &mut buffer);
+        let halt = evaluator.run(Cycle::Maximum(100), |_| true);
+        (halt, evaluator.output().to_vec(), evaluator.cycles)
+    }
+
+    #[test]
+    fn bytecode_should_fold_constant_moves() {
+        let program: Program = Program(vec![
+            Instruction::LABEL(String::from("START")),
+            Instruction::MOV(Source::Literal(1200), Destination::Register(Register::ACC)),
+            Instruction::MOV(Source::Register(Register::ACC), Destination::Register(Register::ACC)),
+            Instruction::MOV(Source::Register(Register::NIL), Destination::Port(Direction::DOWN)),
+            Instruction::ADD(Source::Literal(0)),
+            Instruction::JMP(String::from("START")),
+        ]);
+
+        assert_eq!(Bytecode { ops: vec![
+            Op::SetAcc(999),
+            Op::Nop,
+            Op::Mov(Operand::Constant(0), Target::Port(Direction::DOWN)),
+            Op::Nop,
+            Op::Jump(Condition::Always, Some(0)),
+        ], unknown_label: None, positions: vec![0, 0, 1, 2, 3, 4] }, Bytecode::compile(&program));
+    }
+
+    #[test]
+    fn bytecode_should_evaluate_like_a_node() {
+        let node: Node = Node::new().set_up(Port::new(vec![3, -4, 0, 5, 1]));
+        let programs: Vec<Program> = vec![
+            Program(vec![
+                Instruction::MOV(Source::Port(Direction::UP), Destination::Register(Register::ACC)),
+                Instruction::JEZ(String::from("ZERO")),
+                Instruction::SUB(Source::Literal(1)),
+                Instruction::SAV,
+                Instruction::ADD(Source::Register(Register::ACC)),
+                Instruction::MOV(Source::Register(Register::ACC), Destination::Port(Direction::DOWN)),
+                Instruction::LABEL(String::from("ZERO")),
+            ]),
+            Program(vec![
+                Instruction::JRO(Source::Port(Direction::ANY)),
+                Instruction::MOV(Source::Literal(1), Destination::Port(Direction::LAST)),
+                Instruction::LABEL(String::from("L")),
+                Instruction::NEG,
+                Instruction::MOV(Source::Register(Register::ACC), Destination::Port(Direction::DOWN)),
+                Instruction::SWP,
+            ]),
+            Program(vec![
+                Instruction::LABEL(String::from("START")),
+                Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::DOWN)),
+                Instruction::JMP(String::from("START")),
+            ]),
+        ];
+
+        for program in programs {
+            let expected: Node = node.load(program.clone()).run(Cycle::Maximum(100)).ok().unwrap();
+
+            assert_eq!((Halt::Finished, expected.down.output, expected.cycles), evaluate(&node, &program));
+        }
+    }
+
+    #[test]
+    fn bytecode_should_deadlock_on_unknown_labels() {
+        let node: Node = Node::new();
+        let program: Program = Program(vec![Instruction::JNZ(String::from("L")), Instruction::JEZ(String::from("L"))]);
+
+        assert_eq!(Op::Jump(Condition::NonZero, None), Bytecode::compile(&program).ops[0]);
+        assert_eq!(Some(String::from("L")), Bytecode::compile(&program).unknown_label);
+        assert_eq!(Halt::UnknownLabel, evaluate(&node, &program).0);
+
+        let input: Node = Node::new().set_up(Port::new(vec![1, 2, 3]));
+        let never_taken: Program = Program(vec![
+            Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::DOWN)),
+            Instruction::JEZ(String::from("MISSING")),
+        ]);
+
+        assert_eq!((Halt::UnknownLabel, vec![], 0), evaluate(&input, &never_taken));
+    }
+
+    #[test]
+    fn bytecode_should_start_at_the_program_counter_like_an_executor() {
+        let instructions = vec![
+            Instruction::MOV(Source::Literal(1), Destination::Port(Direction::DOWN)),
+            Instruction::LABEL(String::from("L")),
+            Instruction::MOV(Source::Literal(2), Destination::Port(Direction::DOWN)),
+        ];
+        let program: Program = Program(instructions.clone());
+
+        for pc in 0..4 {
+            let node: Node = Node::new().load(program.clone()).set_pc(pc);
+            let mut buffer = vec![0; 5];
+            let mut executor = Executor::new(&node, &instructions, &mut buffer);
+            let halt = executor.run(Cycle::Maximum(100), |_| true);
+
+            assert_eq!((halt, executor.output().to_vec(), executor.cycles), evaluate(&node, &program));
+        }
+    }
+}
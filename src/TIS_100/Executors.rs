@@ -0,0 +1,315 @@
+//! An `Executor` runs a program on a single node in place.
+//!
+//! The persistent `Node` creates a new node for every instruction it executes,
+//! which is convenient to follow a program step by step but slow when many
+//! candidate programs have to be run. An `Executor` borrows the instructions,
+//! the input of the ports and a buffer for the output, and only keeps indices
+//! into them. Running a program does not allocate.
+//!
+//! An `Executor` behaves like `Node::run`. Only values written to the down
+//! port are recorded, values written to the other ports are discarded.
+
+use super::{Node, Instruction, Source, Destination, Register, Cycle, saturate, unknown_label};
+use super::Streams::Streams;
+
+/// Why an `Executor` stopped running
+#[derive(Debug,PartialEq,Eq,Clone,Copy)]
+pub enum Halt {
+    /// The program waits for input that will never arrive, or has none to wait for
+    Finished,
+    /// A jump targets an unknown label, found before the program runs, like
+    /// `ErrorStatus::UnknownLabel`
+    UnknownLabel,
+    /// The program did not finish within the allowed cycles
+    Timeout,
+    /// The program wrote more values than the output buffer holds
+    Overflow,
+    /// The caller asked to stop
+    Stopped,
+}
+
+/// The effect of executing a single instruction
+enum Step {
+    Executed,
+    Blocked,
+    UnknownLabel,
+    Overflow,
+}
+
+/// An `Executor` runs instructions on borrowed inputs and a borrowed output
+pub struct Executor<'a> {
+    instructions: &'a [Instruction],
+    unknown_label: bool,
+    streams: Streams<'a>,
+    /// The accumulator
+    pub acc: i32,
+    /// The backup register
+    pub bac: i32,
+    /// The number of cycles the executor has run
+    pub cycles: u32,
+    pc: usize,
+}
+
+impl<'a> Executor<'a> {
+    /// Create an `Executor` that starts in the state of `node`, reading the
+    /// input of its ports, running `instructions` and writing the values for
+    /// the down port into `output`
+    pub fn new(node: &'a Node, instructions: &'a [Instruction], output: &'a mut [i32]) -> Executor<'a> {
+        Executor {
+            instructions,
+            unknown_label: unknown_label(instructions).is_some(),
+            streams: Streams::new(node, output),
+            acc: node.acc,
+            bac: node.bac,
+            cycles: node.cycles,
+            pc: node.pc,
+        }
+    }
+
+    /// The values written to the down port so far
+    pub fn output(&self) -> &[i32] {
+        self.streams.output()
+    }
+
+    /// Run the instructions like `Node::run_while`, stopping as soon as
+    /// `proceed` returns false after an executed instruction
+    pub fn run<F>(&mut self, allowed_cycles: Cycle, mut proceed: F) -> Halt where F: FnMut(&Executor) -> bool {
+        if self.unknown_label {
+            return Halt::UnknownLabel;
+        }
+        if self.instructions.iter().all(is_label) {
+            return Halt::Finished;
+        }
+        let instructions = self.instructions;
+
+        loop {
+            match instructions.get(self.pc) {
+                Some(instruction) => match self.execute(instruction) {
+                    Step::Executed => {
+                        self.cycles += instruction.cycles();
+                        if !proceed(self) {
+                            return Halt::Stopped;
+                        }
+                        if !allowed_cycles.should_continu(self.cycles) {
+                            return Halt::Timeout;
+                        }
+                    },
+                    Step::Blocked => return Halt::Finished,
+                    Step::UnknownLabel => return Halt::UnknownLabel,
+                    Step::Overflow => return Halt::Overflow,
+                },
+                None => {
+                    if self.streams.input_available() {
+                        self.pc = 0;
+                    } else {
+                        return Halt::Finished;
+                    }
+                },
+            }
+        }
+    }
+
+    fn execute(&mut self, instruction: &Instruction) -> Step {
+        match *instruction {
+            Instruction::NOP | Instruction::LABEL(_) => self.advance(),
+            Instruction::MOV(ref source, ref destination) => match self.value_from(source) {
+                Some(value) => self.move_value(value, destination),
+                None => Step::Blocked,
+            },
+            Instruction::SWP => {
+                std::mem::swap(&mut self.acc, &mut self.bac);
+                self.advance()
+            },
+            Instruction::SAV => {
+                self.bac = self.acc;
+                self.advance()
+            },
+            Instruction::ADD(ref source) => match self.value_from(source) {
+                Some(value) => {
+                    self.acc = saturate(self.acc.saturating_add(value));
+                    self.advance()
+                },
+                None => Step::Blocked,
+            },
+            Instruction::SUB(ref source) => match self.value_from(source) {
+                Some(value) => {
+                    self.acc = saturate(self.acc.saturating_sub(value));
+                    self.advance()
+                },
+                None => Step::Blocked,
+            },
+            Instruction::NEG => {
+                self.acc = -self.acc;
+                self.advance()
+            },
+            Instruction::JMP(ref label) => self.jump_if(true, label),
+            Instruction::JEZ(ref label) => self.jump_if(self.acc == 0, label),
+            Instruction::JNZ(ref label) => self.jump_if(self.acc != 0, label),
+            Instruction::JGZ(ref label) => self.jump_if(self.acc > 0, label),
+            Instruction::JLZ(ref label) => self.jump_if(self.acc < 0, label),
+            Instruction::JRO(ref source) => match self.value_from(source) {
+                Some(offset) => {
+                    self.jump_relative(offset);
+                    Step::Executed
+                },
+                None => Step::Blocked,
+            },
+        }
+    }
+
+    fn advance(&mut self) -> Step {
+        self.pc += 1;
+        Step::Executed
+    }
+
+    fn value_from(&mut self, source: &Source) -> Option<i32> {
+        match *source {
+            Source::Port(direction) => self.streams.read_from(direction),
+            Source::Register(Register::NIL) => Some(0),
+            Source::Register(Register::ACC) => Some(self.acc),
+            Source::Literal(value) => Some(saturate(value)),
+        }
+    }
+
+    fn move_value(&mut self, value: i32, destination: &Destination) -> Step {
+        match *destination {
+            Destination::Port(direction) => {
+                self.pc += 1;
+                match self.streams.write_to(direction, value) {
+                    Ok(()) => Step::Executed,
+                    Err(_) => Step::Overflow,
+                }
+            },
+            Destination::Register(Register::ACC) => {
+                self.acc = value;
+                self.advance()
+            },
+            Destination::Register(Register::NIL) => self.advance(),
+        }
+    }
+
+    fn jump_if(&mut self, condition: bool, label: &str) -> Step {
+        if !condition {
+            return self.advance();
+        }
+        let target = self.instructions.iter().position(|instruction| match *instruction {
+            Instruction::LABEL(ref name) => name == label,
+            _ => false,
+        });
+        match target {
+            Some(pc) => {
+                self.pc = pc;
+                Step::Executed
+            },
+            None => Step::UnknownLabel,
+        }
+    }
+
+    fn jump_relative(&mut self, offset: i32) {
+        let current = self.instructions[..self.pc].iter().filter(|instruction| !is_label(instruction)).count() as i64;
+        let last = self.instructions.iter().filter(|instruction| !is_label(instruction)).count() as i64 - 1;
+        let target = (current + offset as i64).clamp(0, last) as usize;
+        if let Some(pc) = self.instructions.iter()
+            .enumerate()
+            .filter(|&(_, instruction)| !is_label(instruction))
+            .map(|(index, _)| index)
+            .nth(target) {
+            self.pc = pc;
+        }
+    }
+}
+
+fn is_label(instruction: &Instruction) -> bool {
+    matches!(*instruction, Instruction::LABEL(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Node, Program, Instruction, Source, Destination, Register, Cycle};
+    use super::super::Ports::{Port, Direction};
+    use super::*;
+
+    fn run_both(node: Node, instructions: Vec<Instruction>, capacity: usize) -> (Halt, Vec<i32>, Option<Node>) {
+        let mut buffer = vec![0; capacity];
+        let (halt, output) = {
+            let mut executor = Executor::new(&node, &instructions, &mut buffer);
+            let halt = executor.run(Cycle::Maximum(100), |_| true);
+            (halt, executor.output().to_vec())
+        };
+        (halt, output, node.load(Program(instructions)).run(Cycle::Maximum(100)).ok())
+    }
+
+    #[test]
+    fn executor_should_run_like_a_node() {
+        let node: Node = Node::new().set_up(Port::new(vec![3, -4, 5]));
+        let instructions = vec![
+            Instruction::LABEL(String::from("START")),
+            Instruction::MOV(Source::Port(Direction::UP), Destination::Register(Register::ACC)),
+            Instruction::JLZ(String::from("START")),
+            Instruction::SAV,
+            Instruction::ADD(Source::Register(Register::ACC)),
+            Instruction::SWP,
+            Instruction::SUB(Source::Literal(1)),
+            Instruction::NEG,
+            Instruction::MOV(Source::Register(Register::ACC), Destination::Port(Direction::DOWN)),
+        ];
+
+        let (halt, output, result) = run_both(node, instructions, 10);
+
+        assert_eq!(Halt::Finished, halt);
+        assert_eq!(vec![-2, -4], output);
+        assert_eq!(Some(output), result.map(|node| node.down.output));
+    }
+
+    #[test]
+    fn executor_should_jump_relative_like_a_node() {
+        let node: Node = Node::new().set_up(Port::new(vec![2, 0, 7]));
+        let instructions = vec![
+            Instruction::JRO(Source::Port(Direction::UP)),
+            Instruction::MOV(Source::Literal(1), Destination::Port(Direction::DOWN)),
+            Instruction::LABEL(String::from("L")),
+            Instruction::MOV(Source::Literal(2), Destination::Port(Direction::DOWN)),
+        ];
+
+        let (_, output, result) = run_both(node, instructions, 10);
+
+        assert_eq!(Some(output), result.map(|node| node.down.output));
+    }
+
+    #[test]
+    fn executor_should_report_overflow_and_unknown_labels() {
+        let node: Node = Node::new().set_up(Port::new(vec![1, 2, 3]));
+        let echo = vec![Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::DOWN))];
+        let lost = vec![Instruction::JMP(String::from("NOWHERE"))];
+
+        assert_eq!(Halt::Overflow, run_both(node.clone(), echo, 2).0);
+        assert_eq!(Halt::UnknownLabel, run_both(node, lost, 2).0);
+    }
+
+    #[test]
+    fn executor_should_refuse_an_unknown_label_that_is_never_jumped_to() {
+        let node: Node = Node::new().set_up(Port::new(vec![1, 2, 3]));
+        let instructions = vec![
+            Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::DOWN)),
+            Instruction::JEZ(String::from("MISSING")),
+        ];
+
+        let (halt, output, result) = run_both(node, instructions, 3);
+
+        assert_eq!(Halt::UnknownLabel, halt);
+        assert!(output.is_empty());
+        assert_eq!(None, result);
+    }
+
+    #[test]
+    fn executor_should_stop_when_asked() {
+        let node: Node = Node::new().set_up(Port::new(vec![1, 2, 3]));
+        let echo = vec![Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::DOWN))];
+        let mut buffer = [0; 3];
+        let mut executor = Executor::new(&node, &echo, &mut buffer);
+
+        assert_eq!(Halt::Stopped, executor.run(Cycle::Maximum(100), |executor| executor.output().len() < 2));
+        assert_eq!(&[1, 2], executor.output());
+        assert_eq!(4, executor.cycles);
+    }
+}
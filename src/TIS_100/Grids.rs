@@ -0,0 +1,692 @@
+//! A `Grid` is a two dimensional layout of `Tile`s whose neighbouring ports are
+//! connected. A `Tile` is either a basic execution `Node`, a stack memory
+//! `Stack` or a damaged node that does not communicate at all.
+//!
+//! The connecting ports behave as rendezvous channels: a `Node` that writes to a
+//! port waits until its neighbour has read the value. All nodes step in
+//! lockstep, so a value written in one cycle can be read in the next cycle. As a
+//! consequence a write to a connecting port takes at least two cycles, and
+//! every cycle a node waits on a port counts as well.
+//! Input streams feed the up port of the top row, output streams collect the
+//! values written to the down port of the bottom row.
+
+use super::{Node, Program, Instruction, Destination, Cycle, ErrorStatus, Mode};
+use super::Ports::{Port, Direction};
+use super::Stacks::Stack;
+use super::Images::Image;
+
+/// The real ports of a `Node`, i.e. without `ANY` and `LAST`
+const DIRECTIONS: [Direction; 4] = [Direction::UP, Direction::DOWN, Direction::LEFT, Direction::RIGHT];
+
+/// A `Stream` of values that enters or leaves a `Grid` at a certain column
+#[derive(Debug,PartialEq,Eq,Clone)]
+pub struct Stream {
+    /// The column the stream is attached to
+    pub column: usize,
+    /// The values of the stream
+    pub values: Vec<i32>,
+}
+
+impl Stream {
+    /// Create a `Stream` at `column` with prescribed values
+    pub fn new(column: usize, values: Vec<i32>) -> Stream {
+        Stream { column, values }
+    }
+}
+
+/// A `Tile` is a position in a `Grid`
+#[derive(Debug,PartialEq,Eq,Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum Tile {
+    /// A basic execution node
+    Execution(Node),
+    /// A stack memory node
+    Stack(Stack),
+    /// A damaged node, reading from or writing to it blocks forever
+    Damaged,
+}
+
+/// A value written by a `Node` that waits for a reader
+#[derive(Debug,PartialEq,Eq,Clone,Copy)]
+struct Pending {
+    direction: Direction,
+    value: i32,
+    taken: bool,
+}
+
+/// A `Grid` of `Tile`s that pass values through shared ports
+#[derive(Debug,PartialEq,Eq,Clone)]
+pub struct Grid {
+    /// The number of columns of the grid
+    pub width: usize,
+    /// The number of rows of the grid
+    pub height: usize,
+    /// The tiles of the grid, row by row
+    pub tiles: Vec<Tile>,
+    /// The streams that feed the top row
+    pub inputs: Vec<Stream>,
+    /// The streams that collect from the bottom row
+    pub outputs: Vec<Stream>,
+    /// The number of cycles the grid has run
+    pub cycles: u32,
+    pending: Vec<Option<Pending>>,
+}
+
+impl Grid {
+    /// Create a `Grid` of `width` columns and `height` rows with default `Node`s.
+    /// Panics when the grid has no rows or no columns.
+    pub fn new(width: usize, height: usize) -> Grid {
+        assert!(width > 0 && height > 0, "empty grid of {} by {}", width, height);
+        Grid {
+            width,
+            height,
+            tiles: vec![Tile::Execution(Node::new()); width * height],
+            inputs: vec![],
+            outputs: vec![],
+            cycles: 0,
+            pending: vec![None; width * height],
+        }
+    }
+
+    /// Loads a program in the `Node` at `row` and `column`. Other tiles are
+    /// left as they are. Panics when `row` and `column` lie outside the grid.
+    pub fn load(&self, row: usize, column: usize, program: Program) -> Grid {
+        let mut grid = self.clone();
+        let index = grid.index(row, column);
+        if let Tile::Execution(ref node) = self.tiles[index] {
+            grid.tiles[index] = Tile::Execution(node.load(program));
+        }
+        grid
+    }
+
+    /// Create a `Grid` from self with `programs` loaded into the execution
+    /// nodes, counted row by row. Superfluous programs are ignored.
+    pub fn load_programs(&self, programs: &[Program]) -> Grid {
+        let positions: Vec<(usize, usize)> = (0..self.tiles.len())
+            .filter(|&index| matches!(self.tiles[index], Tile::Execution(_)))
+            .map(|index| (index / self.width, index % self.width))
+            .collect();
+        programs.iter()
+            .zip(positions)
+            .fold(self.clone(), |next_grid, (program, (row, column))| next_grid.load(row, column, program.clone()))
+    }
+
+    /// Create a `Grid` from self with an empty `Stack` at `row` and `column`.
+    /// Panics when `row` and `column` lie outside the grid.
+    pub fn with_stack(&self, row: usize, column: usize) -> Grid {
+        let mut grid = self.clone();
+        let index = grid.index(row, column);
+        grid.tiles[index] = Tile::Stack(Stack::new());
+        grid
+    }
+
+    /// Create a `Grid` from self with a damaged node at `row` and `column`.
+    /// Panics when `row` and `column` lie outside the grid.
+    pub fn with_damaged(&self, row: usize, column: usize) -> Grid {
+        let mut grid = self.clone();
+        let index = grid.index(row, column);
+        grid.tiles[index] = Tile::Damaged;
+        grid
+    }
+
+    /// Create a `Grid` from self with an input stream feeding `column`
+    pub fn with_input(&self, column: usize, values: Vec<i32>) -> Grid {
+        let mut grid = self.clone();
+        grid.inputs.push(Stream::new(column, values));
+        grid
+    }
+
+    /// Create a `Grid` from self with an output stream collecting from `column`
+    pub fn with_output(&self, column: usize) -> Grid {
+        let mut grid = self.clone();
+        grid.outputs.push(Stream::new(column, vec![]));
+        grid
+    }
+
+    /// The `Node` at `row` and `column`, if that tile is an execution node
+    pub fn node(&self, row: usize, column: usize) -> Option<&Node> {
+        match self.tile(row, column) {
+            Some(Tile::Execution(node)) => Some(node),
+            _ => None,
+        }
+    }
+
+    /// The `Stack` at `row` and `column`, if that tile is a stack memory node
+    pub fn stack(&self, row: usize, column: usize) -> Option<&Stack> {
+        match self.tile(row, column) {
+            Some(Tile::Stack(stack)) => Some(stack),
+            _ => None,
+        }
+    }
+
+    /// The values collected by the output stream at `column`, if present
+    pub fn output(&self, column: usize) -> Option<&Vec<i32>> {
+        self.outputs.iter().find(|stream| stream.column == column).map(|stream| &stream.values)
+    }
+
+    /// The `Image` a visualization module attached to the output stream at
+    /// `column` shows, if that stream is present
+    pub fn image(&self, column: usize) -> Option<Image> {
+        self.output(column).map(|values| Image::new().draw(values))
+    }
+
+    /// Run all nodes until every node is stuck, i.e. is waiting in `Mode::READ`
+    /// or `Mode::WRTE`, or is `Mode::IDLE`. Succeeds when the input streams are
+    /// exhausted by then, otherwise reports a deadlock.
+    pub fn run(&self, allowed_cycles: Cycle) -> Result<Grid, ErrorStatus<Grid>> {
+        for tile in &self.tiles {
+            if let Tile::Execution(ref node) = *tile {
+                if let Some(label) = node.program.unknown_label() {
+                    return Err(ErrorStatus::UnknownLabel(label));
+                }
+            }
+        }
+        let mut grid = self.clone();
+
+        loop {
+            match grid.advance() {
+                Some(next_grid) => {
+                    if !allowed_cycles.should_continu(next_grid.cycles) {
+                        return Err(ErrorStatus::Timeout(next_grid));
+                    }
+                    grid = next_grid;
+                },
+                None => {
+                    if grid.inputs_exhausted() {
+                        return Ok(grid);
+                    } else {
+                        return Err(ErrorStatus::Deadlock(grid));
+                    }
+                },
+            }
+        }
+    }
+
+    /// Create a `Grid` from self where every node advanced one cycle
+    pub fn step(&self) -> Grid {
+        self.tick().0
+    }
+
+    /// Create a `Grid` from self where every node advanced one cycle. Returns
+    /// `None` when every node is stuck.
+    pub fn advance(&self) -> Option<Grid> {
+        match self.tick() {
+            (next_grid, true) => Some(next_grid),
+            (_, false) => None,
+        }
+    }
+
+    /// Determine if all values of the input streams have been read
+    pub fn inputs_exhausted(&self) -> bool {
+        self.inputs.iter().all(|stream| stream.values.is_empty())
+    }
+
+    /// Advance every node one cycle. Also reports if anything changed.
+    fn tick(&self) -> (Grid, bool) {
+        let mut grid = Grid { cycles: self.cycles + 1, .. self.clone() };
+        let mut progressed = false;
+
+        // writes that were read in the previous cycle complete
+        for index in 0..grid.pending.len() {
+            if grid.pending[index].is_some_and(|pending| pending.taken) {
+                grid.pending[index] = None;
+            }
+        }
+
+        for stream_index in 0..grid.outputs.len() {
+            progressed |= grid.collect(stream_index);
+        }
+
+        // writes of this cycle can only be read in the next cycle
+        let mut written: Vec<(usize, Pending)> = vec![];
+        for index in 0..grid.tiles.len() {
+            match grid.tiles[index].clone() {
+                Tile::Stack(stack) => progressed |= grid.push(index, stack),
+                Tile::Execution(node) => {
+                    if grid.pending[index].is_some() {
+                        continue;
+                    }
+                    let (next_node, pending) = grid.execute(index, node);
+                    progressed |= next_node.mode == Mode::RUN || next_node.mode == Mode::WRTE;
+                    if let Some(pending) = pending {
+                        written.push((index, pending));
+                    }
+                    grid.tiles[index] = Tile::Execution(next_node);
+                },
+                Tile::Damaged => (),
+            }
+        }
+        for (index, pending) in written {
+            grid.pending[index] = Some(pending);
+        }
+
+        (grid, progressed)
+    }
+
+    /// Let the `node` at `index` execute its current instruction. Returns the
+    /// resulting `Node` and what it wrote to a port, if anything.
+    fn execute(&mut self, index: usize, node: Node) -> (Node, Option<Pending>) {
+        let (staged, instruction) = match next_instruction(&self.stage(index, &node)) {
+            Some(found) => found,
+            None => return (node.set_mode(Mode::IDLE), None),
+        };
+        match staged.execute(instruction.clone()) {
+            Some(executed) => {
+                self.consume(index, &executed);
+                match written_by(&instruction, &executed) {
+                    Some(pending) => (executed.unstaged().set_mode(Mode::WRTE), Some(pending)),
+                    None => (executed.unstaged().set_mode(Mode::RUN), None),
+                }
+            },
+            None => (node.set_mode(Mode::READ), None),
+        }
+    }
+
+    /// Lets the `stack` at `index` take the values its neighbours write to it
+    fn push(&mut self, index: usize, stack: Stack) -> bool {
+        let mut next_stack = stack;
+        let mut progressed = false;
+        for &direction in DIRECTIONS.iter() {
+            if let Some(neighbour) = self.neighbour(index, direction) {
+                if let Some(pending) = self.waiting_in(neighbour, direction.opposite()) {
+                    if let Some(pushed) = next_stack.push(pending.value) {
+                        next_stack = pushed;
+                        self.take(neighbour, direction.opposite());
+                        progressed = true;
+                    }
+                }
+            }
+        }
+        self.tiles[index] = Tile::Stack(next_stack);
+        progressed
+    }
+
+    /// Lets the output stream collect a value written down by the bottom row
+    fn collect(&mut self, stream_index: usize) -> bool {
+        let column = self.outputs[stream_index].column;
+        if column >= self.width {
+            return false;
+        }
+        let index = self.index(self.height - 1, column);
+        match self.waiting_in(index, Direction::DOWN) {
+            Some(pending) => {
+                self.take(index, Direction::DOWN);
+                self.outputs[stream_index].values.push(pending.value);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// The `node` at `index` with the values its neighbours offer on its ports
+    fn stage(&self, index: usize, node: &Node) -> Node {
+        DIRECTIONS.iter().fold(node.clone(), |node, &direction| {
+            let input = self.offered(index, direction).map_or(vec![], |value| vec![value]);
+            node.set_port(direction, Port::new(input))
+        })
+    }
+
+    /// The value offered to the `Node` at `index` on its port in `direction`
+    fn offered(&self, index: usize, direction: Direction) -> Option<i32> {
+        match self.neighbour(index, direction) {
+            Some(neighbour) => match self.tiles[neighbour] {
+                Tile::Stack(ref stack) => stack.top(),
+                Tile::Execution(_) => self.waiting_in(neighbour, direction.opposite()).map(|pending| pending.value),
+                Tile::Damaged => None,
+            },
+            None => {
+                let (row, column) = (index / self.width, index % self.width);
+                if direction == Direction::UP && row == 0 {
+                    self.inputs.iter()
+                        .find(|stream| stream.column == column && !stream.values.is_empty())
+                        .map(|stream| stream.values[0])
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Marks the offered values that the `executed` node at `index` read as taken
+    fn consume(&mut self, index: usize, executed: &Node) {
+        for &direction in DIRECTIONS.iter() {
+            let offered = self.offered(index, direction).is_some();
+            let read = executed.port(direction).is_some_and(|port| !port.available());
+            if !(offered && read) {
+                continue;
+            }
+            match self.neighbour(index, direction) {
+                Some(neighbour) => match self.tiles[neighbour].clone() {
+                    Tile::Stack(stack) => {
+                        if let Some((popped, _)) = stack.pop() {
+                            self.tiles[neighbour] = Tile::Stack(popped);
+                        }
+                    },
+                    Tile::Execution(_) => self.take(neighbour, direction.opposite()),
+                    Tile::Damaged => (),
+                },
+                None => {
+                    let (row, column) = (index / self.width, index % self.width);
+                    if let Some(stream) = self.inputs.iter_mut().find(|stream| direction == Direction::UP && row == 0 && stream.column == column) {
+                        stream.values.remove(0);
+                    }
+                },
+            }
+        }
+    }
+
+    /// The value the `Node` at `index` wrote and that can be read from its side
+    /// in `direction`
+    fn waiting_in(&self, index: usize, direction: Direction) -> Option<Pending> {
+        match self.pending[index] {
+            Some(pending) if !pending.taken && offers(pending.direction, direction) => Some(pending),
+            _ => None,
+        }
+    }
+
+    /// Marks the value the `Node` at `index` wrote as taken from its side in
+    /// `direction`. A value written to `ANY` makes that side the `LAST` port.
+    fn take(&mut self, index: usize, direction: Direction) {
+        if let Some(pending) = self.pending[index] {
+            self.pending[index] = Some(Pending { taken: true, .. pending });
+            if pending.direction == Direction::ANY {
+                if let Tile::Execution(ref node) = self.tiles[index].clone() {
+                    self.tiles[index] = Tile::Execution(node.set_last(direction));
+                }
+            }
+        }
+    }
+
+    /// The `Tile` at `row` and `column`, if that lies inside the grid
+    fn tile(&self, row: usize, column: usize) -> Option<&Tile> {
+        if row < self.height && column < self.width {
+            Some(&self.tiles[self.index(row, column)])
+        } else {
+            None
+        }
+    }
+
+    fn index(&self, row: usize, column: usize) -> usize {
+        assert!(row < self.height && column < self.width, "no tile at row {} and column {}", row, column);
+        row * self.width + column
+    }
+
+    /// The index of the neighbour of the tile at `index` in `direction`
+    fn neighbour(&self, index: usize, direction: Direction) -> Option<usize> {
+        let row = index / self.width;
+        let column = index % self.width;
+        match direction {
+            Direction::UP if row > 0 => Some(index - self.width),
+            Direction::DOWN if row + 1 < self.height => Some(index + self.width),
+            Direction::LEFT if column > 0 => Some(index - 1),
+            Direction::RIGHT if column + 1 < self.width => Some(index + 1),
+            _ => None,
+        }
+    }
+}
+
+impl Node {
+    /// Create a `Node` from self without any values on its ports
+    fn unstaged(&self) -> Node {
+        DIRECTIONS.iter().fold(self.clone(), |node, &direction| node.set_port(direction, Port::new(vec![])))
+    }
+}
+
+/// Determine if a value written to `written` can be read from the neighbour on
+/// the side of `direction`
+fn offers(written: Direction, direction: Direction) -> bool {
+    written == direction || written == Direction::ANY
+}
+
+/// The value the `executed` node wrote with `instruction`, if any
+fn written_by(instruction: &Instruction, executed: &Node) -> Option<Pending> {
+    match *instruction {
+        Instruction::MOV(_, Destination::Port(direction)) => {
+            let direction = if direction == Direction::LAST { executed.last? } else { direction };
+            let port = if direction == Direction::ANY { &executed.up } else { executed.port(direction)? };
+            port.output.last().map(|&value| Pending { direction, value, taken: false })
+        },
+        _ => None,
+    }
+}
+
+/// The first instruction of `node` from its program counter on that is not a
+/// `LABEL`, wrapping around to the start of the program once. Labels take no
+/// cycles, so the node moves past them without spending a tick.
+fn next_instruction(node: &Node) -> Option<(Node, Instruction)> {
+    let mut current = node.clone();
+    let mut wrapped = false;
+    loop {
+        match current.fetch_instruction() {
+            Some(Instruction::LABEL(_)) => current = current.increment_pc(),
+            Some(instruction) => return Some((current, instruction)),
+            None if !wrapped => {
+                wrapped = true;
+                current = current.set_pc(0);
+            },
+            None => return None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Program, Instruction, Source, Destination, Register, Cycle, ErrorStatus, Mode};
+    use super::super::Ports::Direction;
+    use super::*;
+
+    #[test]
+    fn grid_should_pass_values_between_nodes() {
+        let grid: Grid = Grid::new(2, 1)
+            .load(0, 0, Program(vec![
+                Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::RIGHT)),
+            ]))
+            .load(0, 1, Program(vec![
+                Instruction::MOV(Source::Port(Direction::LEFT), Destination::Register(Register::ACC)),
+                Instruction::ADD(Source::Register(Register::ACC)),
+                Instruction::MOV(Source::Register(Register::ACC), Destination::Port(Direction::DOWN)),
+            ]))
+            .with_input(0, vec![1, 2, 3])
+            .with_output(1);
+
+        match grid.run(Cycle::Maximum(100)) {
+            Ok(result) => assert_eq!(Some(&vec![2, 4, 6]), result.output(1)),
+            Err(_) => panic!(),
+        }
+    }
+
+    #[test]
+    fn grid_should_block_on_damaged_nodes() {
+        let grid: Grid = Grid::new(2, 1)
+            .with_damaged(0, 1)
+            .load(0, 0, Program(vec![
+                Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::RIGHT)),
+            ]))
+            .with_input(0, vec![1, 2]);
+
+        match grid.run(Cycle::Maximum(100)) {
+            Err(ErrorStatus::Deadlock(result)) => assert_eq!(Tile::Damaged, result.tiles[1]),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn grid_should_have_no_tiles_outside_it() {
+        let grid: Grid = Grid::new(3, 2).with_stack(1, 2);
+
+        assert_eq!(Some(&Node::new()), grid.node(0, 2));
+        assert_eq!(None, grid.node(0, 3));
+        assert_eq!(None, grid.node(2, 0));
+        assert_eq!(Some(&Stack::new()), grid.stack(1, 2));
+        assert_eq!(None, grid.stack(1, 3));
+    }
+
+    #[test]
+    #[should_panic(expected = "no tile at row 0 and column 3")]
+    fn grid_should_not_load_outside_it() {
+        Grid::new(3, 2).load(0, 3, Program(vec![Instruction::NEG]));
+    }
+
+    #[test]
+    #[should_panic(expected = "empty grid")]
+    fn grid_should_have_rows_and_columns() {
+        Grid::new(1, 0);
+    }
+
+    #[test]
+    fn grid_should_load_programs_row_by_row() {
+        let program: Program = Program(vec![Instruction::NEG]);
+        let grid: Grid = Grid::new(2, 2).with_stack(0, 1).load_programs(&[Program(vec![]), program.clone()]);
+
+        assert_eq!(Some(&Node::new().load(program)), grid.node(1, 0));
+    }
+
+    #[test]
+    fn grid_should_take_two_cycles_for_a_write() {
+        let grid: Grid = Grid::new(1, 1)
+            .load(0, 0, Program(vec![
+                Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::DOWN)),
+            ]))
+            .with_input(0, vec![1, 2, 3])
+            .with_output(0);
+
+        match grid.run(Cycle::Maximum(100)) {
+            Ok(result) => {
+                assert_eq!(Some(&vec![1, 2, 3]), result.output(0));
+                assert_eq!(6, result.cycles);
+            },
+            Err(_) => panic!(),
+        }
+    }
+
+    #[test]
+    fn grid_should_not_spend_cycles_on_labels() {
+        let echo = Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::DOWN));
+        let cycles = |program: Program| Grid::new(1, 1)
+            .load(0, 0, program)
+            .with_input(0, vec![1, 2, 3])
+            .with_output(0)
+            .run(Cycle::Maximum(100))
+            .ok()
+            .map(|result| result.cycles);
+
+        assert!(cycles(Program(vec![echo.clone()])).is_some());
+        assert_eq!(
+            cycles(Program(vec![echo.clone()])),
+            cycles(Program(vec![Instruction::LABEL(String::from("START")), echo])));
+    }
+
+    #[test]
+    fn grid_should_show_modes_of_nodes() {
+        let grid: Grid = Grid::new(2, 1)
+            .load(0, 0, Program(vec![
+                Instruction::MOV(Source::Literal(1), Destination::Port(Direction::RIGHT)),
+            ]))
+            .load(0, 1, Program(vec![
+                Instruction::MOV(Source::Port(Direction::UP), Destination::Register(Register::ACC)),
+            ]));
+
+        let next: Grid = grid.step();
+
+        assert_eq!(Mode::WRTE, next.node(0, 0).unwrap().mode);
+        assert_eq!(Mode::READ, next.node(0, 1).unwrap().mode);
+    }
+
+    #[test]
+    fn grid_should_pass_values_written_to_ANY_to_the_reader() {
+        let grid: Grid = Grid::new(2, 2)
+            .load(0, 0, Program(vec![
+                Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::ANY)),
+                Instruction::MOV(Source::Literal(0), Destination::Port(Direction::LAST)),
+            ]))
+            .load(1, 0, Program(vec![
+                Instruction::ADD(Source::Port(Direction::UP)),
+                Instruction::ADD(Source::Port(Direction::UP)),
+                Instruction::MOV(Source::Register(Register::ACC), Destination::Port(Direction::DOWN)),
+            ]))
+            .with_input(0, vec![3])
+            .with_output(0);
+
+        match grid.run(Cycle::Maximum(100)) {
+            Ok(result) => {
+                assert_eq!(Some(&vec![3]), result.output(0));
+                assert_eq!(Some(Direction::DOWN), result.node(0, 0).unwrap().last);
+            },
+            Err(_) => panic!(),
+        }
+    }
+
+    #[test]
+    fn grid_should_reverse_values_through_a_stack() {
+        let grid: Grid = Grid::new(1, 2)
+            .load(0, 0, Program(vec![
+                Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::DOWN)),
+                Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::DOWN)),
+                Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::DOWN)),
+                Instruction::MOV(Source::Port(Direction::DOWN), Destination::Port(Direction::UP)),
+            ]))
+            .with_stack(1, 0)
+            .with_input(0, vec![1, 2, 3]);
+
+        match grid.run(Cycle::Maximum(100)) {
+            Ok(result) => {
+                assert_eq!(Some(&Stack { values: vec![1, 2] }), result.stack(1, 0));
+                assert_eq!(None, result.node(1, 0));
+            },
+            Err(_) => panic!(),
+        }
+    }
+
+    #[test]
+    fn grid_should_block_reads_from_an_empty_stack() {
+        let grid: Grid = Grid::new(2, 1)
+            .load(0, 0, Program(vec![
+                Instruction::MOV(Source::Port(Direction::RIGHT), Destination::Register(Register::ACC)),
+            ]))
+            .with_stack(0, 1);
+
+        let next: Grid = grid.step();
+
+        assert_eq!(Mode::READ, next.node(0, 0).unwrap().mode);
+    }
+
+    #[test]
+    fn grid_should_detect_deadlock() {
+        let grid: Grid = Grid::new(2, 1)
+            .load(0, 0, Program(vec![
+                Instruction::MOV(Source::Port(Direction::RIGHT), Destination::Register(Register::ACC)),
+                Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::RIGHT)),
+            ]))
+            .load(0, 1, Program(vec![
+                Instruction::MOV(Source::Port(Direction::LEFT), Destination::Port(Direction::LEFT)),
+            ]))
+            .with_input(0, vec![1, 2]);
+
+        match grid.run(Cycle::Maximum(100)) {
+            Err(ErrorStatus::Deadlock(_)) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn grid_should_report_an_unknown_label() {
+        let grid: Grid = Grid::new(1, 1)
+            .load(0, 0, Program(vec![Instruction::JMP(String::from("MISSING"))]));
+
+        match grid.run(Cycle::Maximum(100)) {
+            Err(ErrorStatus::UnknownLabel(label)) => assert_eq!("MISSING", label),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn grid_should_timeout() {
+        let grid: Grid = Grid::new(1, 1)
+            .load(0, 0, Program(vec![Instruction::NOP]));
+
+        match grid.run(Cycle::Maximum(100)) {
+            Err(ErrorStatus::Timeout(_)) => (),
+            _ => panic!(),
+        }
+    }
+}
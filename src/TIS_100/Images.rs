@@ -0,0 +1,180 @@
+//! `Images` models the T31 visualization module.
+//!
+//! The module decodes a stream of values into a picture of `IMAGE_WIDTH` by
+//! `IMAGE_HEIGHT` pixels. The stream consists of runs: an x coordinate, a y
+//! coordinate and a sequence of colors that are drawn from left to right. A
+//! negative value ends the run. Pixels outside of the picture are ignored.
+
+use std::io::{Write, Result};
+
+/// The number of columns of an `Image`
+pub const IMAGE_WIDTH: usize = 30;
+
+/// The number of rows of an `Image`
+pub const IMAGE_HEIGHT: usize = 18;
+
+/// The `Color`s the visualization module can display
+#[derive(Debug,PartialEq,Eq,Clone,Copy)]
+pub enum Color {
+    /// Color 0
+    Black,
+    /// Color 1
+    DarkGrey,
+    /// Color 2
+    BrightGrey,
+    /// Color 3
+    White,
+    /// Color 4
+    Red,
+}
+
+impl Color {
+    /// The `Color` drawn for `value`. Unknown values draw black.
+    pub fn from_value(value: i32) -> Color {
+        match value {
+            1 => Color::DarkGrey,
+            2 => Color::BrightGrey,
+            3 => Color::White,
+            4 => Color::Red,
+            _ => Color::Black,
+        }
+    }
+
+    /// The red, green and blue components of this `Color`
+    pub fn rgb(&self) -> (u8, u8, u8) {
+        match *self {
+            Color::Black => (0, 0, 0),
+            Color::DarkGrey => (85, 85, 85),
+            Color::BrightGrey => (170, 170, 170),
+            Color::White => (255, 255, 255),
+            Color::Red => (255, 0, 0),
+        }
+    }
+}
+
+/// What the decoder of the draw protocol expects next
+#[derive(Debug,PartialEq,Eq,Clone,Copy)]
+enum Expecting {
+    X,
+    Y(i32),
+    Color(i32, i32),
+}
+
+/// An `Image` as shown by the visualization module
+#[derive(Debug,PartialEq,Eq,Clone)]
+pub struct Image {
+    /// The pixels of the image, row by row
+    pub pixels: Vec<Color>,
+}
+
+impl Default for Image {
+    fn default() -> Image {
+        Image::new()
+    }
+}
+
+impl Image {
+    /// Create a black `Image`
+    pub fn new() -> Image {
+        Image { pixels: vec![Color::Black; IMAGE_WIDTH * IMAGE_HEIGHT] }
+    }
+
+    /// Create an `Image` from rows of color values, e.g. to describe an
+    /// expected image. Missing pixels are black.
+    pub fn from_rows(rows: Vec<Vec<i32>>) -> Image {
+        let mut image = Image::new();
+        for (y, row) in rows.iter().enumerate().take(IMAGE_HEIGHT) {
+            for (x, &value) in row.iter().enumerate().take(IMAGE_WIDTH) {
+                image.pixels[y * IMAGE_WIDTH + x] = Color::from_value(value);
+            }
+        }
+        image
+    }
+
+    /// The `Color` of the pixel at `x` and `y`
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * IMAGE_WIDTH + x]
+    }
+
+    /// Create an `Image` from self with `values` drawn according to the draw
+    /// protocol of the visualization module
+    pub fn draw(&self, values: &[i32]) -> Image {
+        let mut image = self.clone();
+        let mut expecting = Expecting::X;
+        for &value in values {
+            expecting = if value < 0 {
+                Expecting::X
+            } else {
+                match expecting {
+                    Expecting::X => Expecting::Y(value),
+                    Expecting::Y(x) => Expecting::Color(x, value),
+                    Expecting::Color(x, y) => {
+                        if (x as usize) < IMAGE_WIDTH && (y as usize) < IMAGE_HEIGHT {
+                            image.pixels[(y as usize) * IMAGE_WIDTH + (x as usize)] = Color::from_value(value);
+                        }
+                        Expecting::Color(x + 1, y)
+                    },
+                }
+            }
+        }
+        image
+    }
+
+    /// Write this `Image` as a plain PPM file
+    pub fn write_ppm<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writeln!(writer, "P3")?;
+        writeln!(writer, "{} {}", IMAGE_WIDTH, IMAGE_HEIGHT)?;
+        writeln!(writer, "255")?;
+        for row in self.pixels.chunks(IMAGE_WIDTH) {
+            let line: Vec<String> = row.iter()
+                .map(|color| {
+                    let (red, green, blue) = color.rgb();
+                    format!("{} {} {}", red, green, blue)
+                })
+                .collect();
+            writeln!(writer, "{}", line.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image_should_draw_runs_of_colors() {
+        let image: Image = Image::new().draw(&[1, 2, 3, 4, -1, 0, 0, 3]);
+
+        assert_eq!(Color::White, image.pixel(1, 2));
+        assert_eq!(Color::Red, image.pixel(2, 2));
+        assert_eq!(Color::Black, image.pixel(3, 2));
+        assert_eq!(Color::White, image.pixel(0, 0));
+    }
+
+    #[test]
+    fn image_should_ignore_pixels_outside_of_the_image() {
+        let image: Image = Image::new().draw(&[29, 17, 3, 3, -1, 40, 0, 3]);
+
+        assert_eq!(Color::White, image.pixel(29, 17));
+        assert_eq!(1, image.pixels.iter().filter(|&&color| color == Color::White).count());
+    }
+
+    #[test]
+    fn image_should_be_described_by_rows() {
+        let image: Image = Image::from_rows(vec![vec![0, 4]]);
+
+        assert_eq!(Image::new().draw(&[1, 0, 4]), image);
+    }
+
+    #[test]
+    fn image_should_be_written_as_ppm() {
+        let mut data: Vec<u8> = vec![];
+
+        Image::new().draw(&[0, 0, 4]).write_ppm(&mut data).unwrap();
+
+        let ppm = String::from_utf8(data).unwrap();
+        assert!(ppm.starts_with("P3\n30 18\n255\n255 0 0 0 0 0"));
+        assert_eq!(3 + IMAGE_HEIGHT, ppm.lines().count());
+    }
+}
@@ -2,6 +2,46 @@
 //!
 //! They can be used as a `Source` and `Destination` for certain `Instruction`s.
 
+use std::fmt::{Display,Formatter,Error};
+
+/// The `Direction` of a port as seen from a `Node`
+#[derive(Debug,PartialEq,Eq,Clone,Copy)]
+pub enum Direction {
+    /// The port above the node
+    UP,
+    /// The port below the node
+    DOWN,
+    /// The port left of the node
+    LEFT,
+    /// The port right of the node
+    RIGHT,
+    /// Whichever port is first available
+    ANY,
+    /// The port last used by `ANY`
+    LAST,
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Direction {
+    /// The `Direction` pointing the other way. `ANY` and `LAST` are their own
+    /// opposite.
+    pub fn opposite(&self) -> Direction {
+        match *self {
+            Direction::UP => Direction::DOWN,
+            Direction::DOWN => Direction::UP,
+            Direction::LEFT => Direction::RIGHT,
+            Direction::RIGHT => Direction::LEFT,
+            Direction::ANY => Direction::ANY,
+            Direction::LAST => Direction::LAST,
+        }
+    }
+}
+
 /// A `Port` can be read from and write to
 #[derive(Debug,PartialEq,Eq,Clone)]
 pub struct Port {
@@ -14,23 +54,23 @@ pub struct Port {
 impl Port {
     /// Create a port with a number of readable values
     pub fn new(input: Vec<i32>) -> Port {
-        Port { input: input, output: vec![] }
+        Port { input, output: vec![] }
     }
 
     /// Create a port with prescribed input and output
     pub fn with(input: Vec<i32>, output: Vec<i32>) -> Port {
-        Port { input: input, output: output }
+        Port { input, output }
     }
 
     /// Read from this `Port`. Will return a `PortReadResult::Success` when a
     /// value is available, otherwise a `PortReadResult::Failure`
     pub fn read(&self) -> Option<(Port,i32)> {
-        if self.input.len() > 0 {
+        if !self.input.is_empty() {
             let mut result_input = vec![];
             for index in 1..self.input.len() {
                 result_input.push(self.input[index]);
             }
-            Some((Port::with(result_input, vec![]), self.input[0]))
+            Some((Port::with(result_input, self.output.clone()), self.input[0]))
         } else {
             None
         }
@@ -38,7 +78,7 @@ impl Port {
 
     /// Determine if self is available for reading
     pub fn available(&self) -> bool {
-        self.input.len() > 0
+        !self.input.is_empty()
     }
 
     /// Write to this `Port`. Will always succeed and return the Port as it is
@@ -75,6 +115,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn read_from_a_port_should_keep_output() {
+        let port: Port = Port::with(vec![0], vec![3]);
+
+        match port.read() {
+            Some((next_port, _)) => assert_eq!(Port::with(vec![], vec![3]), next_port),
+            None => panic!(),
+        }
+    }
+
     #[test]
     fn write_to_a_port_should_work_correctly() {
         let port: Port = Port::with(vec![1, 2], vec![3]);
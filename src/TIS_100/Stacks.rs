@@ -0,0 +1,84 @@
+//! A `Stack` models the T30 stack memory node.
+//!
+//! It has no program of its own. Neighbouring nodes push values by writing to
+//! the port facing the stack, and pop values by reading from it. A write blocks
+//! while the stack is full, a read blocks while the stack is empty.
+
+/// The number of values a `Stack` can hold
+pub const STACK_CAPACITY: usize = 15;
+
+/// A `Stack` holds up to `STACK_CAPACITY` values
+#[derive(Debug,PartialEq,Eq,Clone)]
+pub struct Stack {
+    /// The values on the stack, the last value is the top of the stack
+    pub values: Vec<i32>,
+}
+
+impl Default for Stack {
+    fn default() -> Stack {
+        Stack::new()
+    }
+}
+
+impl Stack {
+    /// Create an empty `Stack`
+    pub fn new() -> Stack {
+        Stack { values: vec![] }
+    }
+
+    /// Determine if self can not hold another value
+    pub fn is_full(&self) -> bool {
+        self.values.len() >= STACK_CAPACITY
+    }
+
+    /// The value on top of self, if any
+    pub fn top(&self) -> Option<i32> {
+        self.values.last().cloned()
+    }
+
+    /// Push `value` on this `Stack`. Returns `None` when the stack is full
+    pub fn push(&self, value: i32) -> Option<Stack> {
+        if self.is_full() {
+            None
+        } else {
+            let mut values = self.values.clone();
+            values.push(value);
+            Some(Stack { values })
+        }
+    }
+
+    /// Pop the top value of this `Stack`. Returns `None` when the stack is empty
+    pub fn pop(&self) -> Option<(Stack,i32)> {
+        self.top().map(|value| (Stack { values: self.values[..self.values.len() - 1].to_vec() }, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stack_should_pop_values_in_reverse_order() {
+        let stack: Stack = Stack::new().push(1).unwrap().push(2).unwrap();
+
+        match stack.pop() {
+            Some((next_stack, value)) => {
+                assert_eq!(2, value);
+                assert_eq!(Stack { values: vec![1] }, next_stack);
+            },
+            None => panic!(),
+        }
+    }
+
+    #[test]
+    fn stack_should_not_pop_when_empty() {
+        assert_eq!(None, Stack::new().pop());
+    }
+
+    #[test]
+    fn stack_should_not_push_beyond_capacity() {
+        let stack: Stack = Stack { values: vec![0; STACK_CAPACITY] };
+
+        assert_eq!(None, stack.push(1));
+    }
+}
@@ -0,0 +1,155 @@
+//! `Streams` hold the port state of a node that runs in place.
+//!
+//! Both an `Executors::Executor` and a `Bytecodes::Evaluator` read the input
+//! of the ports of a node and record what it writes to the down port. Keeping
+//! that state in one type gives both the same port semantics.
+
+use super::{Node, saturate, ANY_READ_ORDER, ANY_WRITE_ORDER};
+use super::Ports::Direction;
+use super::Executors::Halt;
+
+/// The ports of a node in the order `Streams` keeps them
+const PORTS: [Direction; 4] = [Direction::UP, Direction::DOWN, Direction::LEFT, Direction::RIGHT];
+
+/// The borrowed input of the ports of a node, how much of it has been read,
+/// and a borrowed buffer for the values written to the down port
+pub struct Streams<'a> {
+    inputs: [&'a [i32]; 4],
+    read: [usize; 4],
+    output: &'a mut [i32],
+    written: usize,
+    last: Option<Direction>,
+}
+
+impl<'a> Streams<'a> {
+    /// Create `Streams` that read the input of the ports of `node` and write
+    /// the values for the down port into `output`
+    pub fn new(node: &'a Node, output: &'a mut [i32]) -> Streams<'a> {
+        Streams {
+            inputs: [&node.up.input, &node.down.input, &node.left.input, &node.right.input],
+            read: [0; 4],
+            output,
+            written: 0,
+            last: node.last,
+        }
+    }
+
+    /// The values written to the down port so far
+    pub fn output(&self) -> &[i32] {
+        &self.output[..self.written]
+    }
+
+    /// Reads a value from the port in `direction` like `Node` does, or `None`
+    /// when the port has no input left
+    pub fn read_from(&mut self, direction: Direction) -> Option<i32> {
+        match direction {
+            Direction::ANY => ANY_READ_ORDER.iter()
+                .cloned()
+                .find(|&candidate| self.available(candidate))
+                .and_then(|candidate| {
+                    self.last = Some(candidate);
+                    self.read_from(candidate)
+                }),
+            Direction::LAST => match self.last {
+                Some(last) => self.read_from(last),
+                None => Some(0),
+            },
+            _ => {
+                let slot = slot(direction);
+                let value = self.inputs[slot].get(self.read[slot]).cloned();
+                if value.is_some() {
+                    self.read[slot] += 1;
+                }
+                value.map(saturate)
+            },
+        }
+    }
+
+    /// Determine if any port has input left
+    pub fn input_available(&self) -> bool {
+        PORTS.iter().any(|&direction| self.available(direction))
+    }
+
+    /// Writes `value` to the port in `direction` like `Node` does. Every port
+    /// of a lone node is attached, so `ANY` hands the value to `UP`. Only
+    /// values for the down port are recorded, an `Err(Halt::Overflow)` tells
+    /// that the output buffer is full.
+    pub fn write_to(&mut self, direction: Direction, value: i32) -> Result<(), Halt> {
+        if direction == Direction::ANY {
+            self.last = Some(ANY_WRITE_ORDER[0]);
+            return self.write_to(ANY_WRITE_ORDER[0], value);
+        }
+        if self.writes_down(direction) {
+            if self.written == self.output.len() {
+                return Err(Halt::Overflow);
+            }
+            self.output[self.written] = value;
+            self.written += 1;
+        }
+        Ok(())
+    }
+
+    fn available(&self, direction: Direction) -> bool {
+        let slot = slot(direction);
+        self.read[slot] < self.inputs[slot].len()
+    }
+
+    fn writes_down(&self, direction: Direction) -> bool {
+        match direction {
+            Direction::DOWN => true,
+            Direction::LAST => self.last == Some(Direction::DOWN),
+            _ => false,
+        }
+    }
+}
+
+fn slot(direction: Direction) -> usize {
+    match direction {
+        Direction::UP => 0,
+        Direction::DOWN => 1,
+        Direction::LEFT => 2,
+        _ => 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Node;
+    use super::super::Ports::{Port, Direction};
+    use super::*;
+
+    #[test]
+    fn streams_should_read_ANY_in_read_priority_and_remember_it() {
+        let node: Node = Node::new().set_up(Port::new(vec![1])).set_left(Port::new(vec![2]));
+        let mut buffer = [0; 2];
+        let mut streams = Streams::new(&node, &mut buffer);
+
+        assert_eq!(Some(2), streams.read_from(Direction::ANY));
+        assert_eq!(None, streams.read_from(Direction::LAST));
+        assert_eq!(Some(1), streams.read_from(Direction::ANY));
+        assert!(!streams.input_available());
+    }
+
+    #[test]
+    fn streams_should_record_only_the_down_port() {
+        let node: Node = Node::new();
+        let mut buffer = [0; 1];
+        let mut streams = Streams::new(&node, &mut buffer);
+
+        assert_eq!(Ok(()), streams.write_to(Direction::UP, 3));
+        assert_eq!(Ok(()), streams.write_to(Direction::DOWN, 4));
+        assert_eq!(Err(Halt::Overflow), streams.write_to(Direction::DOWN, 5));
+        assert_eq!(&[4], streams.output());
+    }
+
+    #[test]
+    fn streams_should_write_ANY_to_a_single_port() {
+        let node: Node = Node::new();
+        let mut buffer = [0; 1];
+        let mut streams = Streams::new(&node, &mut buffer);
+
+        assert_eq!(Ok(()), streams.write_to(Direction::ANY, 3));
+        assert_eq!(Ok(()), streams.write_to(Direction::LAST, 4));
+        assert!(streams.output().is_empty());
+    }
+}
@@ -26,9 +26,15 @@
 //! ```
 
 pub mod Ports;
+pub mod Stacks;
+pub mod Grids;
+pub mod Images;
+pub mod Executors;
+pub mod Bytecodes;
+mod Streams;
 
-use std::fmt::{Debug,Formatter,Error};
-use self::Ports::Port;
+use std::fmt::{Debug,Display,Formatter,Error};
+use self::Ports::{Port,Direction};
 
 /// A `Node` models the basic execution node in TIS-100. You change a node state
 /// by running `Program`s on it or executing an `Instruction` on it.
@@ -36,13 +42,35 @@ use self::Ports::Port;
 pub struct Node {
     /// The accumulator for the basic execution node.
     pub acc: i32,
-    /// The up `Port` used for reading
+    /// The up `Port`
     pub up: Port,
-    /// The down `Port` used for writing
+    /// The down `Port`
     pub down: Port,
+    /// The left `Port`
+    pub left: Port,
+    /// The right `Port`
+    pub right: Port,
+    /// The number of cycles this node has run
+    pub cycles: u32,
+    /// The `Mode` the node is in
+    pub mode: Mode,
     bac: i32,
     pc: usize,
     program: Program,
+    last: Option<Direction>,
+}
+
+/// The `Mode` of a `Node` as shown in the game
+#[derive(Debug,PartialEq,Eq,Clone,Copy)]
+pub enum Mode {
+    /// The node is executing instructions
+    RUN,
+    /// The node waits for a value to read from a port
+    READ,
+    /// The node waits for a neighbour to read the value it wrote to a port
+    WRTE,
+    /// The node has no instructions to execute
+    IDLE,
 }
 
 /// A `Program` is a sequence of `Instruction`s
@@ -50,18 +78,18 @@ pub struct Program(pub Vec<Instruction>);
 
 impl PartialEq for Program {
     fn eq(&self, other: &Program) -> bool {
-        let Program(ref self_instructions) = *self;
-        let Program(ref other_instructions) = *other;
+        let Program(self_instructions) = self;
+        let Program(other_instructions) = other;
         self_instructions.len() == other_instructions.len() &&
             same(self_instructions, other_instructions)
     }
 }
 
-fn same<T: Eq>(left: &Vec<T>, right: &Vec<T>) -> bool {
+fn same<T: Eq>(left: &[T], right: &[T]) -> bool {
     assert!(left.len() == right.len());
     for index in 0..(left.len()) {
-        let ref left_item = left[index];
-        let ref right_item = right[index];
+        let left_item = &left[index];
+        let right_item = &right[index];
         if left_item != right_item {
             return false
         }
@@ -82,6 +110,34 @@ impl Debug for Program {
     }
 }
 
+/// Displays a `Program` as TIS-100 assembly, one instruction per line. A
+/// `LABEL` shares the line of the instruction that follows it when that line
+/// stays short enough to fit in the game.
+impl Display for Program {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        let Program(instructions) = self;
+        let mut lines: Vec<String> = vec![];
+        let mut index = 0;
+        while index < instructions.len() {
+            let line = format!("{}", instructions[index]);
+            match (&instructions[index], instructions.get(index + 1)) {
+                (&Instruction::LABEL(_), Some(next)) if !matches!(*next, Instruction::LABEL(_)) => {
+                    let combined = format!("{} {}", line, next);
+                    if combined.len() <= MAXIMUM_LINE_LENGTH {
+                        lines.push(combined);
+                        index += 1;
+                    } else {
+                        lines.push(line);
+                    }
+                },
+                _ => lines.push(line),
+            }
+            index += 1;
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
 impl Clone for Program {
     fn clone(&self) -> Program {
         let mut clone_instructions: Vec<Instruction> = vec![];
@@ -108,19 +164,87 @@ pub enum Instruction {
     ADD(Source),
     /// Subtracts value from `Source` from accumulator (acc), storing result in acc
     SUB(Source),
+    /// Negates the value of the accumulator (acc)
+    NEG,
+    /// Marks a jump target in a `Program`. Does not take up a cycle
+    LABEL(String),
+    /// Unconditionally jumps to the `LABEL` with the same name
+    JMP(String),
+    /// Jumps to the `LABEL` when the accumulator (acc) equals zero
+    JEZ(String),
+    /// Jumps to the `LABEL` when the accumulator (acc) does not equal zero
+    JNZ(String),
+    /// Jumps to the `LABEL` when the accumulator (acc) is greater than zero
+    JGZ(String),
+    /// Jumps to the `LABEL` when the accumulator (acc) is less than zero
+    JLZ(String),
+    /// Jumps relative to this instruction by the value from `Source`. Labels are
+    /// not counted and the target is clamped to the first and last instruction
+    JRO(Source),
 }
 
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match *self {
+            Instruction::NOP => write!(f, "NOP"),
+            Instruction::MOV(ref source, ref destination) => write!(f, "MOV {}, {}", source, destination),
+            Instruction::SWP => write!(f, "SWP"),
+            Instruction::SAV => write!(f, "SAV"),
+            Instruction::ADD(ref source) => write!(f, "ADD {}", source),
+            Instruction::SUB(ref source) => write!(f, "SUB {}", source),
+            Instruction::NEG => write!(f, "NEG"),
+            Instruction::LABEL(ref label) => write!(f, "{}:", label),
+            Instruction::JMP(ref label) => write!(f, "JMP {}", label),
+            Instruction::JEZ(ref label) => write!(f, "JEZ {}", label),
+            Instruction::JNZ(ref label) => write!(f, "JNZ {}", label),
+            Instruction::JGZ(ref label) => write!(f, "JGZ {}", label),
+            Instruction::JLZ(ref label) => write!(f, "JLZ {}", label),
+            Instruction::JRO(ref source) => write!(f, "JRO {}", source),
+        }
+    }
+}
+
+/// The smallest value a TIS-100 can represent
+pub const MINIMUM_VALUE: i32 = -999;
+
+/// The largest value a TIS-100 can represent
+pub const MAXIMUM_VALUE: i32 = 999;
+
+/// The maximum number of characters the game allows on a line
+pub const MAXIMUM_LINE_LENGTH: usize = 18;
+
+/// Clamps `value` to the range of values a TIS-100 can represent
+pub fn saturate(value: i32) -> i32 {
+    value.clamp(MINIMUM_VALUE, MAXIMUM_VALUE)
+}
+
+/// The order in which `Direction::ANY` checks ports for reading
+const ANY_READ_ORDER: [Direction; 4] = [Direction::LEFT, Direction::RIGHT, Direction::UP, Direction::DOWN];
+
+/// The order in which `Direction::ANY` offers values to ports for writing
+const ANY_WRITE_ORDER: [Direction; 4] = [Direction::UP, Direction::LEFT, Direction::RIGHT, Direction::DOWN];
+
 /// `Source` are either ports, registers or literals
 #[derive(Debug,PartialEq,Eq,Clone)]
 pub enum Source {
-    /// A port, will always be the up port
-    Port,
+    /// A port in a certain `Direction`
+    Port(Direction),
     /// A register
     Register(Register),
     /// A literal value
     Literal(i32)
 }
 
+impl Display for Source {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match *self {
+            Source::Port(ref direction) => write!(f, "{}", direction),
+            Source::Register(ref register) => write!(f, "{}", register),
+            Source::Literal(value) => write!(f, "{}", value),
+        }
+    }
+}
+
 /// Different types of Registers known in TIS-100
 #[derive(Debug,PartialEq,Eq,Clone,Copy)]
 pub enum Register {
@@ -131,29 +255,48 @@ pub enum Register {
 }
 
 
+impl Display for Register {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "{:?}", self)
+    }
+}
+
 /// `Destination` are either ports or registers
 #[derive(Debug,PartialEq,Eq,Clone)]
 pub enum Destination {
-    /// A Port, will always be the DOWN port
-    Port,
+    /// A port in a certain `Direction`
+    Port(Direction),
     /// A register
     Register(Register),
 }
 
 
-/// The `ErrorStatus` a `Program` of an unsuccessful run on a certain `Node`
-pub enum ErrorStatus {
+impl Display for Destination {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match *self {
+            Destination::Port(ref direction) => write!(f, "{}", direction),
+            Destination::Register(ref register) => write!(f, "{}", register),
+        }
+    }
+}
+
+/// The `ErrorStatus` a `Program` of an unsuccessful run on a certain `Node`, or
+/// of an unsuccessful run of a `Grids::Grid`
+pub enum ErrorStatus<S = Node> {
     /// a deadlock occurred
-    Deadlock(Node),
+    Deadlock(S),
     /// a timeout occurred
-    Timeout(Node)
+    Timeout(S),
+    /// a jump targets a label the program does not define, found when the
+    /// run starts
+    UnknownLabel(String),
 }
 
 /// Determines how many cycles to run a program
 pub enum Cycle {
     /// Run until the input arguments run out
     Indefinetly,
-    /// Run for a maximum of cycles
+    /// Run for a maximum of machine cycles
     Maximum(u32),
 }
 
@@ -161,11 +304,39 @@ impl Cycle {
     fn should_continu(&self, cycle_count: u32) -> bool {
         match *self {
             Cycle::Indefinetly => true,
-            Cycle::Maximum(maximum) => cycle_count < maximum,
+            Cycle::Maximum(maximum) => cycle_count <= maximum,
         }
     }
 }
 
+impl Instruction {
+    /// The number of machine cycles executing this instruction takes when it
+    /// does not have to wait on a port. A `LABEL` takes no time and a write to
+    /// a port takes an extra cycle to hand over the value.
+    pub fn cycles(&self) -> u32 {
+        match *self {
+            Instruction::LABEL(_) => 0,
+            Instruction::MOV(_, Destination::Port(_)) => 2,
+            _ => 1,
+        }
+    }
+
+    /// Determine if this instruction reads from a port
+    fn reads_port(&self) -> bool {
+        matches!(*self,
+                 Instruction::MOV(Source::Port(_), _) |
+                 Instruction::ADD(Source::Port(_)) |
+                 Instruction::SUB(Source::Port(_)) |
+                 Instruction::JRO(Source::Port(_)))
+    }
+}
+
+
+impl Default for Node {
+    fn default() -> Node {
+        Node::new()
+    }
+}
 
 impl Node {
     /// Create a `Node` with defaults for accumulator, backup registers, program counter and program
@@ -177,35 +348,65 @@ impl Node {
             program: Program(vec![]),
             up: Port::new(vec![]),
             down: Port::new(vec![]),
+            left: Port::new(vec![]),
+            right: Port::new(vec![]),
+            cycles: 0,
+            mode: Mode::IDLE,
+            last: None,
         }
     }
 
     /// Loads a program in this `Node`
     pub fn load(&self, program: Program) -> Node {
-        Node { program: program.clone(), up: self.up.clone(), down: self.down.clone(), .. *self }
+        Node { program, .. self.clone() }
     }
 
-    /// Run the loaded program, returning an calculation state
+    /// Run the loaded program, returning an calculation state. Every executed
+    /// `Instruction` adds its `Instruction::cycles` to the cycles of the node.
+    ///
+    /// A run finishes cleanly when the node has to wait for input that will
+    /// never arrive, leaving the node in `Mode::READ`.
+    #[allow(clippy::result_large_err)]
     pub fn run(&self, allowed_cycles: Cycle) -> Result<Node, ErrorStatus> {
-        let mut node = Node { program: self.program.clone(), up: self.up.clone(), down: self.down.clone(), .. *self };
+        self.run_while(allowed_cycles, |_| true)
+    }
+
+    /// Run the loaded program like `run`, but stop early as soon as `proceed`
+    /// returns false for the node after an executed instruction. The node is
+    /// returned as it was at that moment.
+    #[allow(clippy::result_large_err)]
+    pub fn run_while<F>(&self, allowed_cycles: Cycle, mut proceed: F) -> Result<Node, ErrorStatus> where F: FnMut(&Node) -> bool {
+        if let Some(label) = self.program.unknown_label() {
+            return Err(ErrorStatus::UnknownLabel(label));
+        }
+        if self.program.instruction_positions().is_empty() {
+            return Ok(self.set_mode(Mode::IDLE));
+        }
+        let mut node = self.set_mode(Mode::RUN);
 
-        let mut cycle_count: u32 = 0;
         loop {
             match node.fetch_instruction() {
                 Some(instruction) => {
+                    let cycles = node.cycles + instruction.cycles();
+                    let reads_port = instruction.reads_port();
                     match node.execute(instruction) {
-                        Some(next_node) => node = next_node,
+                        Some(next_node) => {
+                            let next_node = Node { cycles, .. next_node };
+                            if !proceed(&next_node) {
+                                return Ok(next_node);
+                            }
+                            if !allowed_cycles.should_continu(cycles) {
+                                return Err(ErrorStatus::Timeout(next_node));
+                            }
+                            node = next_node
+                        },
+                        None if reads_port => return Ok(node.set_mode(Mode::READ)),
                         None => return Err(ErrorStatus::Deadlock(node)),
                     }
                 }
                 None => {
-                    cycle_count += 1;
-                    if node.up.available() {
-                        if allowed_cycles.should_continu(cycle_count) {
-                            node = node.set_pc(0);
-                        } else {
-                            return Err(ErrorStatus::Timeout(node));
-                        }
+                    if node.input_available() {
+                        node = node.set_pc(0);
                     } else {
                         break;
                     }
@@ -213,11 +414,11 @@ impl Node {
             }
         }
 
-        Ok(Node { program: node.program.clone(), .. node })
+        Ok(node)
     }
 
     fn fetch_instruction(&self) -> Option<Instruction> {
-        let Program(ref instructions) = self.program;
+        let Program(instructions) = &self.program;
         if self.pc < instructions.len() {
             Some(instructions[self.pc].clone())
         } else {
@@ -225,38 +426,88 @@ impl Node {
         }
     }
 
+    /// Determine if any of the ports of self is available for reading
+    fn input_available(&self) -> bool {
+        self.up.available() || self.down.available() || self.left.available() || self.right.available()
+    }
+
     /// Create a `Node` from self with the program counter incremented
     fn increment_pc(&self) -> Node {
-        Node { pc: self.pc + 1, program: self.program.clone(), up: self.up.clone(), down: self.down.clone(), .. *self }
+        Node { pc: self.pc + 1, .. self.clone() }
     }
 
     /// Create a `Node` from self with a prescribed program counter value
     fn set_pc(&self, pc: usize) -> Node {
-        Node { pc: pc, program: self.program.clone(), up: self.up.clone(), down: self.down.clone(), .. *self }
+        Node { pc, .. self.clone() }
     }
 
     /// Create a `Node` from self with a prescribed accumulator register value
     fn set_acc(&self, acc: i32) -> Node {
-        Node { acc: acc, program: self.program.clone(), up: self.up.clone(), down: self.down.clone(), .. *self }
+        Node { acc, .. self.clone() }
     }
 
     /// Create a `Node` from self with a prescribed backup register value
     fn set_bac(&self, bac: i32) -> Node {
-        Node { bac: bac, program: self.program.clone(), up: self.up.clone(), down: self.down.clone(), .. *self }
+        Node { bac, .. self.clone() }
     }
 
-    /// Create a `Node` from self with a prescribed down port
+    /// Create a `Node` from self with a prescribed mode
+    fn set_mode(&self, mode: Mode) -> Node {
+        Node { mode, .. self.clone() }
+    }
+
+    /// Create a `Node` from self with a prescribed last port
+    fn set_last(&self, last: Direction) -> Node {
+        Node { last: Some(last), .. self.clone() }
+    }
+
+    /// Create a `Node` from self with a prescribed up port
     pub fn set_up(&self, up: Port) -> Node {
-        Node { up: up, program: self.program.clone(), down: self.down.clone(), .. *self }
+        Node { up, .. self.clone() }
     }
 
     /// Create a `Node` from self with a prescribed down port
-    fn set_down(&self, down: Port) -> Node {
-        Node { down: down, program: self.program.clone(), up: self.up.clone(), .. *self }
+    pub fn set_down(&self, down: Port) -> Node {
+        Node { down, .. self.clone() }
+    }
+
+    /// Create a `Node` from self with a prescribed left port
+    pub fn set_left(&self, left: Port) -> Node {
+        Node { left, .. self.clone() }
+    }
+
+    /// Create a `Node` from self with a prescribed right port
+    pub fn set_right(&self, right: Port) -> Node {
+        Node { right, .. self.clone() }
+    }
+
+    /// The `Port` of self in the prescribed direction. `ANY` and `LAST` are
+    /// not actual ports, so they have no `Port`.
+    fn port(&self, direction: Direction) -> Option<&Port> {
+        match direction {
+            Direction::UP => Some(&self.up),
+            Direction::DOWN => Some(&self.down),
+            Direction::LEFT => Some(&self.left),
+            Direction::RIGHT => Some(&self.right),
+            Direction::ANY | Direction::LAST => None,
+        }
+    }
+
+    /// Create a `Node` from self with a prescribed port in `direction`
+    fn set_port(&self, direction: Direction, port: Port) -> Node {
+        match direction {
+            Direction::UP => self.set_up(port),
+            Direction::DOWN => self.set_down(port),
+            Direction::LEFT => self.set_left(port),
+            Direction::RIGHT => self.set_right(port),
+            Direction::ANY | Direction::LAST => self.clone(),
+        }
     }
 
     /// Execute the `instruction` on this `Node`. Returns a `Node` that reflects
-    /// the changes the `instruction` would have on this `Node`.
+    /// the changes the `instruction` would have on this `Node`. Returns `None`
+    /// when the `instruction` can not be executed, e.g. when a jump targets a
+    /// label that is not present in the loaded `Program`.
     pub fn execute(&self, instruction: Instruction) -> Option<Node> {
         match instruction {
             Instruction::NOP => self.nop(),
@@ -265,6 +516,14 @@ impl Node {
             Instruction::SAV => self.save(),
             Instruction::ADD(source) => self.add(source),
             Instruction::SUB(source) => self.subtract(source),
+            Instruction::NEG => self.negate(),
+            Instruction::LABEL(_) => self.nop(),
+            Instruction::JMP(label) => self.jump_if(true, &label),
+            Instruction::JEZ(label) => self.jump_if(self.acc == 0, &label),
+            Instruction::JNZ(label) => self.jump_if(self.acc != 0, &label),
+            Instruction::JGZ(label) => self.jump_if(self.acc > 0, &label),
+            Instruction::JLZ(label) => self.jump_if(self.acc < 0, &label),
+            Instruction::JRO(source) => self.jump_relative(source),
         }
     }
 
@@ -273,24 +532,66 @@ impl Node {
     }
 
     fn mov(&self, source: Source, destination: Destination) -> Option<Node> {
-        self.value_from(source).and_then(|(next_up_port, value)| self.set_up(next_up_port).move_value(value, destination))
+        self.value_from(source).and_then(|(next_node, value)| next_node.move_value(value, destination))
     }
 
-    fn value_from(&self, source: Source) -> Option<(Port,i32)> {
+    fn value_from(&self, source: Source) -> Option<(Node,i32)> {
         match source {
-            Source::Port => self.up.read(),
-            Source::Register(Register::NIL) => Some((self.up.clone(),0)),
-            Source::Register(Register::ACC) => Some((self.up.clone(),self.acc)),
-            Source::Literal(value) => Some((self.up.clone(),value)),
+            Source::Port(direction) => self.read_from(direction),
+            Source::Register(Register::NIL) => Some((self.clone(),0)),
+            Source::Register(Register::ACC) => Some((self.clone(),self.acc)),
+            Source::Literal(value) => Some((self.clone(),saturate(value))),
+        }
+    }
+
+    /// Reads a value from the port in `direction`. `ANY` reads from the first
+    /// available port in the order `LEFT`, `RIGHT`, `UP`, `DOWN` and remembers
+    /// that port as `LAST`. Reading from `LAST` before any `ANY` read acts as
+    /// reading from `NIL`.
+    fn read_from(&self, direction: Direction) -> Option<(Node,i32)> {
+        match direction {
+            Direction::ANY => ANY_READ_ORDER.iter()
+                .find(|&&candidate| self.port(candidate).is_some_and(|port| port.available()))
+                .and_then(|&candidate| self.read_from(candidate)
+                          .map(|(next_node, value)| (next_node.set_last(candidate), value))),
+            Direction::LAST => match self.last {
+                Some(last) => self.read_from(last),
+                None => Some((self.clone(),0)),
+            },
+            _ => self.port(direction)
+                .and_then(|port| port.read())
+                .map(|(next_port, value)| (self.set_port(direction, next_port), saturate(value))),
+        }
+    }
+
+    /// Writes `value` to the port in `direction`. `ANY` hands the value to the
+    /// first attached port in the order `UP`, `LEFT`, `RIGHT`, `DOWN` and
+    /// remembers that port as `LAST`. Writing to `LAST` before any `ANY` acts
+    /// as writing to `NIL`.
+    ///
+    /// A lone `Node` does not know which neighbour reads, and every port is
+    /// attached, so on a lone `Node` `ANY` always means `UP`. A `Grid` hands
+    /// the value to whichever neighbour reads it first.
+    fn write_to(&self, direction: Direction, value: i32) -> Node {
+        match direction {
+            Direction::ANY => match ANY_WRITE_ORDER.iter().find(|&&candidate| self.port(candidate).is_some()) {
+                Some(&candidate) => self.write_to(candidate, value).set_last(candidate),
+                None => self.clone(),
+            },
+            Direction::LAST => match self.last {
+                Some(last) => self.write_to(last, value),
+                None => self.clone(),
+            },
+            _ => match self.port(direction) {
+                Some(port) => self.set_port(direction, port.write(value)),
+                None => self.clone(),
+            },
         }
     }
 
     fn move_value(&self, value: i32, destination: Destination) -> Option<Node> {
         match destination {
-            Destination::Port => {
-                let next_down = self.down.write(value);
-                Some(self.increment_pc().set_down(next_down))
-            },
+            Destination::Port(direction) => Some(self.increment_pc().write_to(direction, value)),
             Destination::Register(Register::ACC) => Some(self.increment_pc().set_acc(value)),
             _ => self.nop(),
         }
@@ -308,19 +609,83 @@ impl Node {
     }
 
     fn add(&self, source: Source) -> Option<Node>{
-        self.value_from(source).map(|(next_up_port, value)| self.set_up(next_up_port).add_value(value))
+        self.value_from(source).map(|(next_node, value)| next_node.add_value(value))
     }
 
     fn add_value(&self, value: i32) -> Node {
-        self.increment_pc().set_acc(self.acc + value)
+        self.increment_pc().set_acc(saturate(self.acc.saturating_add(value)))
     }
 
     fn subtract(&self, source: Source) -> Option<Node> {
-        self.value_from(source).map(|(next_up_port, value)| self.set_up(next_up_port).subtract_value(value))
+        self.value_from(source).map(|(next_node, value)| next_node.subtract_value(value))
     }
 
     fn subtract_value(&self, value: i32) -> Node {
-        self.increment_pc().set_acc(self.acc - value)
+        self.increment_pc().set_acc(saturate(self.acc.saturating_sub(value)))
+    }
+
+    fn negate(&self) -> Option<Node> {
+        Some(self.increment_pc().set_acc(-self.acc))
+    }
+
+    fn jump_if(&self, condition: bool, label: &str) -> Option<Node> {
+        if condition {
+            self.program.label_position(label).map(|pc| self.set_pc(pc))
+        } else {
+            self.nop()
+        }
+    }
+
+    fn jump_relative(&self, source: Source) -> Option<Node> {
+        self.value_from(source).map(|(next_node, offset)| {
+            let positions = self.program.instruction_positions();
+            if positions.is_empty() {
+                return next_node;
+            }
+            let current = positions.iter().take_while(|&&position| position < self.pc).count() as i64;
+            let last = (positions.len() - 1) as i64;
+            let target = (current + offset as i64).clamp(0, last) as usize;
+            next_node.set_pc(positions[target])
+        })
+    }
+}
+
+/// The first label a jump in `instructions` targets that they do not define
+fn unknown_label(instructions: &[Instruction]) -> Option<&String> {
+    instructions.iter()
+        .filter_map(|instruction| match *instruction {
+            Instruction::JMP(ref label) |
+            Instruction::JEZ(ref label) |
+            Instruction::JNZ(ref label) |
+            Instruction::JGZ(ref label) |
+            Instruction::JLZ(ref label) => Some(label),
+            _ => None,
+        })
+        .find(|&label| !instructions.iter().any(|instruction| matches!(*instruction, Instruction::LABEL(ref name) if name == label)))
+}
+
+impl Program {
+    /// The position of the `LABEL` named `label`, if present
+    fn label_position(&self, label: &str) -> Option<usize> {
+        let Program(instructions) = self;
+        instructions.iter().position(|instruction| match *instruction {
+            Instruction::LABEL(ref name) => name == label,
+            _ => false,
+        })
+    }
+
+    /// The first label a jump targets that this program does not define
+    fn unknown_label(&self) -> Option<String> {
+        let Program(instructions) = self;
+        unknown_label(instructions).cloned()
+    }
+
+    /// The positions of all instructions that are not a `LABEL`
+    fn instruction_positions(&self) -> Vec<usize> {
+        let Program(instructions) = self;
+        (0..instructions.len())
+            .filter(|&index| !matches!(instructions[index], Instruction::LABEL(_)))
+            .collect()
     }
 }
 
@@ -402,7 +767,7 @@ mod tests {
     fn node_should_execute_MOV_from_Literal_to_Port_correctly() {
         let node: Node = Node::new();
         let instruction: Instruction = Instruction::MOV(Source::Literal(1),
-                                                        Destination::Port);
+                                                        Destination::Port(Direction::DOWN));
 
         let next: Node = node.execute(instruction).unwrap();
 
@@ -412,7 +777,7 @@ mod tests {
     #[test]
     fn node_should_execute_MOV_from_Port_to_ACC_correctly() {
         let node: Node = Node::new().set_up(Port::new(vec![1]));
-        let instruction: Instruction = Instruction::MOV(Source::Port,
+        let instruction: Instruction = Instruction::MOV(Source::Port(Direction::UP),
                                                         Destination::Register(Register::ACC));
 
         let next: Node = node.execute(instruction).unwrap();
@@ -420,6 +785,90 @@ mod tests {
         assert_eq!(node_with(1, 0, 1, vec![], vec![]), next);
     }
 
+    #[test]
+    fn node_should_execute_MOV_from_LEFT_to_RIGHT_correctly() {
+        let node: Node = Node::new().set_left(Port::new(vec![1]));
+        let instruction: Instruction = Instruction::MOV(Source::Port(Direction::LEFT),
+                                                        Destination::Port(Direction::RIGHT));
+
+        let next: Node = node.execute(instruction).unwrap();
+
+        assert_eq!(Port::with(vec![], vec![]), next.left);
+        assert_eq!(Port::with(vec![], vec![1]), next.right);
+    }
+
+    #[test]
+    fn node_should_execute_MOV_from_ANY_in_read_priority() {
+        let node: Node = Node::new()
+            .set_up(Port::new(vec![1]))
+            .set_right(Port::new(vec![2]));
+        let instruction: Instruction = Instruction::MOV(Source::Port(Direction::ANY),
+                                                        Destination::Register(Register::ACC));
+
+        let next: Node = node.execute(instruction).unwrap();
+
+        assert_eq!(2, next.acc);
+        assert_eq!(Some(Direction::RIGHT), next.last);
+    }
+
+    #[test]
+    fn node_should_execute_MOV_from_LAST_as_port_last_read_by_ANY() {
+        let node: Node = Node::new()
+            .set_up(Port::new(vec![1, 2]))
+            .set_left(Port::new(vec![3, 4]));
+        let program: Vec<Instruction> = vec![
+            Instruction::MOV(Source::Port(Direction::ANY), Destination::Register(Register::ACC)),
+            Instruction::ADD(Source::Port(Direction::LAST)),
+        ];
+
+        let next: Node = node
+            .execute(program[0].clone())
+            .and_then(|next_node| next_node.execute(program[1].clone()))
+            .unwrap();
+
+        assert_eq!(Port::with(vec![1, 2], vec![]), next.up);
+        assert_eq!(Some(Direction::LEFT), next.last);
+        assert_eq!(7, next.acc);
+    }
+
+    #[test]
+    fn node_should_execute_MOV_to_ANY_on_a_single_port() {
+        let node: Node = Node::new();
+        let instruction: Instruction = Instruction::MOV(Source::Literal(5),
+                                                        Destination::Port(Direction::ANY));
+
+        let next: Node = node.execute(instruction).unwrap();
+
+        assert_eq!(Port::with(vec![], vec![5]), next.up);
+        assert_eq!(Port::with(vec![], vec![]), next.down);
+        assert_eq!(Port::with(vec![], vec![]), next.left);
+        assert_eq!(Port::with(vec![], vec![]), next.right);
+        assert_eq!(Some(Direction::UP), next.last);
+    }
+
+    #[test]
+    fn node_should_execute_MOV_to_ANY_as_UP_on_a_lone_node() {
+        let node: Node = Node::new().set_down(Port::with(vec![], vec![1]));
+
+        let next: Node = node
+            .execute(Instruction::MOV(Source::Literal(5), Destination::Port(Direction::ANY))).unwrap()
+            .execute(Instruction::MOV(Source::Literal(6), Destination::Port(Direction::LAST))).unwrap();
+
+        assert_eq!(Port::with(vec![], vec![5, 6]), next.up);
+        assert_eq!(Port::with(vec![], vec![1]), next.down);
+    }
+
+    #[test]
+    fn node_should_execute_MOV_from_LAST_without_ANY_as_NIL() {
+        let node: Node = Node::new().set_acc(1).set_up(Port::new(vec![2]));
+        let instruction: Instruction = Instruction::MOV(Source::Port(Direction::LAST),
+                                                        Destination::Register(Register::ACC));
+
+        let next: Node = node.execute(instruction).unwrap();
+
+        assert_eq!(node_with(0, 0, 1, vec![2], vec![]), next);
+    }
+
     #[test]
     fn node_should_execute_SWP_correctly() {
         let node: Node = Node::new().set_acc(1);
@@ -460,14 +909,117 @@ mod tests {
         assert_eq!(node_with(1, 0, 1, vec![], vec![]), next);
     }
 
+    #[test]
+    fn node_should_execute_NEG_correctly() {
+        let node: Node = Node::new().set_acc(3);
+        let instruction: Instruction = Instruction::NEG;
+
+        let next: Node = node.execute(instruction).unwrap();
+
+        assert_eq!(node_with(-3, 0, 1, vec![], vec![]), next);
+    }
+
+    #[test]
+    fn node_should_saturate_ADD_correctly() {
+        let node: Node = Node::new().set_acc(600);
+        let instruction: Instruction = Instruction::ADD(Source::Register(Register::ACC));
+
+        let next: Node = node.execute(instruction).unwrap();
+
+        assert_eq!(node_with(999, 0, 1, vec![], vec![]), next);
+    }
+
+    #[test]
+    fn node_should_saturate_SUB_correctly() {
+        let node: Node = Node::new().set_acc(-600);
+        let instruction: Instruction = Instruction::SUB(Source::Literal(600));
+
+        let next: Node = node.execute(instruction).unwrap();
+
+        assert_eq!(node_with(-999, 0, 1, vec![], vec![]), next);
+    }
+
+    #[test]
+    fn node_should_saturate_literals_and_port_values_correctly() {
+        let node: Node = Node::new().set_up(Port::new(vec![-2000]));
+
+        let from_literal: Node = node.execute(Instruction::MOV(Source::Literal(1000), Destination::Port(Direction::DOWN))).unwrap();
+        let from_port: Node = node.execute(Instruction::MOV(Source::Port(Direction::UP), Destination::Register(Register::ACC))).unwrap();
+
+        assert_eq!(vec![999], from_literal.down.output);
+        assert_eq!(-999, from_port.acc);
+    }
+
+    #[test]
+    fn node_should_execute_JMP_to_label_correctly() {
+        let program: Program = Program(vec![
+            Instruction::NOP,
+            Instruction::LABEL(String::from("TARGET")),
+            Instruction::NOP,
+        ]);
+        let node: Node = Node::new().load(program).set_pc(2);
+        let instruction: Instruction = Instruction::JMP(String::from("TARGET"));
+
+        let next: Node = node.execute(instruction).unwrap();
+
+        assert_eq!(1, next.pc);
+    }
+
+    #[test]
+    fn node_should_not_execute_JEZ_when_accumulator_is_not_zero() {
+        let program: Program = Program(vec![Instruction::LABEL(String::from("TARGET"))]);
+        let node: Node = Node::new().load(program).set_acc(1).set_pc(1);
+        let instruction: Instruction = Instruction::JEZ(String::from("TARGET"));
+
+        let next: Node = node.execute(instruction).unwrap();
+
+        assert_eq!(2, next.pc);
+    }
+
+    #[test]
+    fn node_should_not_execute_jump_to_unknown_label() {
+        let node: Node = Node::new();
+        let instruction: Instruction = Instruction::JMP(String::from("UNKNOWN"));
+
+        assert_eq!(None, node.execute(instruction));
+    }
+
+    #[test]
+    fn node_should_report_an_unknown_label_before_running() {
+        let program: Program = Program(vec![
+            Instruction::MOV(Source::Literal(1), Destination::Port(Direction::DOWN)),
+            Instruction::JEZ(String::from("MISSING")),
+        ]);
+
+        match Node::new().load(program).run(Cycle::Maximum(10)) {
+            Err(ErrorStatus::UnknownLabel(label)) => assert_eq!("MISSING", label),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn node_should_execute_JRO_skipping_labels_and_clamping() {
+        let program: Program = Program(vec![
+            Instruction::NOP,
+            Instruction::LABEL(String::from("SKIPPED")),
+            Instruction::JRO(Source::Register(Register::ACC)),
+            Instruction::NOP,
+        ]);
+        let node: Node = Node::new().load(program).set_pc(2);
+
+        assert_eq!(0, node.set_acc(-1).execute(Instruction::JRO(Source::Register(Register::ACC))).unwrap().pc);
+        assert_eq!(0, node.set_acc(-5).execute(Instruction::JRO(Source::Register(Register::ACC))).unwrap().pc);
+        assert_eq!(3, node.set_acc(5).execute(Instruction::JRO(Source::Register(Register::ACC))).unwrap().pc);
+    }
+
     #[test]
     fn programs_should_differ_when_different_size() {
-        assert_eq!(false, Program(vec![]) == Program(vec![Instruction::SAV]));
+        assert_ne!(Program(vec![]), Program(vec![Instruction::SAV]));
     }
 
     #[test]
     fn programs_should_differ_when_different_instructions() {
-        assert_eq!(false, Program(vec![Instruction::SWP]) == Program(vec![Instruction::SAV]));
+        assert_ne!(Program(vec![Instruction::SWP]), Program(vec![Instruction::SAV]));
     }
 
     #[test]
@@ -486,26 +1038,26 @@ mod tests {
         let node: Node = Node::new().load(program);
 
         match node.run(Cycle::Indefinetly) {
-            Ok(_) => assert!(true),
-            Err(_) => assert!(false),
+            Ok(_) => (),
+            Err(_) => panic!(),
         }
     }
 
     #[test]
     fn node_should_execute_program_so_long_as_there_is_input() {
         let program: Program = Program(vec![// calculate 4 * source - 1
-            Instruction::MOV(Source::Port, Destination::Register(Register::ACC)),
+            Instruction::MOV(Source::Port(Direction::UP), Destination::Register(Register::ACC)),
             Instruction::ADD(Source::Register(Register::ACC)),
             Instruction::ADD(Source::Register(Register::ACC)),
             Instruction::SUB(Source::Literal(1)),
-            Instruction::MOV(Source::Register(Register::ACC), Destination::Port),
+            Instruction::MOV(Source::Register(Register::ACC), Destination::Port(Direction::DOWN)),
         ]);
         let node: Node = Node::new().set_up(Port::new(vec![1, 2])).load(program);
 
         match node.run(Cycle::Indefinetly) {
             Ok(result_node) =>
                 assert_eq!(Port::with(vec![], vec![3, 7]), result_node.down),
-            Err(_) => assert!(false),
+            Err(_) => panic!(),
         }
     }
 
@@ -514,11 +1066,91 @@ mod tests {
         let program: Program = Program(vec![Instruction::NOP]);
         let node: Node = Node::new().set_up(Port::new(vec![1, 2])).load(program);
 
-        match node.run(Cycle::Maximum(100)) {
-            Ok(_) => assert!(false),
-            Err(_) => assert!(true),
+        assert!(node.run(Cycle::Maximum(100)).is_err());
+    }
+
+    #[test]
+    fn node_should_execute_program_with_loop_correctly() {
+        let program: Program = Program(vec![// count down from 3 to 0
+            Instruction::MOV(Source::Literal(3), Destination::Register(Register::ACC)),
+            Instruction::LABEL(String::from("LOOP")),
+            Instruction::MOV(Source::Register(Register::ACC), Destination::Port(Direction::DOWN)),
+            Instruction::SUB(Source::Literal(1)),
+            Instruction::JNZ(String::from("LOOP")),
+        ]);
+        let node: Node = Node::new().load(program);
+
+        match node.run(Cycle::Maximum(20)) {
+            Ok(result_node) =>
+                assert_eq!(Port::with(vec![], vec![3, 2, 1]), result_node.down),
+            Err(_) => panic!(),
         }
     }
 
+    #[test]
+    fn node_should_finish_cleanly_when_waiting_on_exhausted_input() {
+        let program: Program = Program(vec![
+            Instruction::ADD(Source::Port(Direction::UP)),
+            Instruction::ADD(Source::Port(Direction::UP)),
+            Instruction::MOV(Source::Register(Register::ACC), Destination::Port(Direction::DOWN)),
+        ]);
+        let node: Node = Node::new().set_up(Port::new(vec![1, 2, 3])).load(program);
+
+        match node.run(Cycle::Indefinetly) {
+            Ok(result_node) => {
+                assert_eq!(Mode::READ, result_node.mode);
+                assert_eq!(vec![3], result_node.down.output);
+            },
+            Err(_) => panic!(),
+        }
+    }
+
+    #[test]
+    fn node_without_instructions_should_be_idle() {
+        let program: Program = Program(vec![Instruction::LABEL(String::from("NOTHING"))]);
+        let node: Node = Node::new().set_up(Port::new(vec![1])).load(program);
+
+        match node.run(Cycle::Indefinetly) {
+            Ok(result_node) => assert_eq!(Mode::IDLE, result_node.mode),
+            Err(_) => panic!(),
+        }
+    }
+
+    #[test]
+    fn node_should_count_cycles_of_executed_instructions() {
+        let program: Program = Program(vec![
+            Instruction::LABEL(String::from("START")),
+            Instruction::MOV(Source::Port(Direction::UP), Destination::Register(Register::ACC)),
+            Instruction::ADD(Source::Register(Register::ACC)),
+            Instruction::MOV(Source::Register(Register::ACC), Destination::Port(Direction::DOWN)),
+        ]);
+        let node: Node = Node::new().set_up(Port::new(vec![1, 2])).load(program);
+
+        match node.run(Cycle::Indefinetly) {
+            Ok(result_node) => assert_eq!(8, result_node.cycles),
+            Err(_) => panic!(),
+        }
+    }
+
+    #[test]
+    fn node_should_allow_a_run_of_exactly_the_maximum_cycles() {
+        let program: Program = Program(vec![Instruction::ADD(Source::Port(Direction::UP))]);
+        let node: Node = Node::new().set_up(Port::new(vec![1, 2])).load(program);
+
+        assert!(node.run(Cycle::Maximum(2)).is_ok());
+        assert!(node.run(Cycle::Maximum(1)).is_err());
+    }
+
+    #[test]
+    fn node_should_timeout_execution_program_that_jumps_back_indefinitely() {
+        let program: Program = Program(vec![
+            Instruction::LABEL(String::from("LOOP")),
+            Instruction::JMP(String::from("LOOP")),
+        ]);
+        let node: Node = Node::new().load(program);
+
+        assert!(node.run(Cycle::Maximum(100)).is_err());
+    }
+
 }
 
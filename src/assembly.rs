@@ -0,0 +1,360 @@
+//! The `assembly` module translates between `Program`s and the assembly text
+//! the TIS-100 game uses.
+//!
+//! The syntax is case insensitive. A `#` starts a comment, a name followed by
+//! `:` declares a label and a `!` at the start of a line marks a breakpoint,
+//! which is ignored. Operands are separated by commas, spaces or both.
+//!
+//! A `Program` displays as assembly text that can be pasted into the game.
+//! `render` also checks that the text stays within the limits of a node.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate tis_100_superoptimizer;
+//!
+//! use tis_100_superoptimizer::assembly::parse;
+//! use tis_100_superoptimizer::TIS_100::{Program,Instruction,Source,Destination};
+//! use tis_100_superoptimizer::TIS_100::Ports::Direction;
+//!
+//! fn main() {
+//!     let program: Program = parse("mov up, down # echo").unwrap();
+//!
+//!     assert_eq!(Program(vec![
+//!         Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::DOWN)),
+//!     ]), program);
+//! }
+//! ```
+
+use std::fmt::{Display, Formatter, Error};
+use super::TIS_100::{Program, Instruction, Source, Destination, Register};
+use super::TIS_100::Ports::Direction;
+pub use super::TIS_100::MAXIMUM_LINE_LENGTH;
+
+/// The maximum number of lines the game allows in a node
+pub const MAXIMUM_LINES: usize = 15;
+
+/// A `Violation` of the limits the game puts on the assembly text of a node
+#[derive(Debug,PartialEq,Eq,Clone)]
+pub enum Violation {
+    /// The text has the contained number of lines, more than `MAXIMUM_LINES`
+    TooManyLines(usize),
+    /// The line, starting at 1, has the contained number of characters, more
+    /// than `MAXIMUM_LINE_LENGTH`
+    LineTooLong(usize, usize),
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match *self {
+            Violation::TooManyLines(lines) =>
+                write!(f, "{} lines exceed the maximum of {}", lines, MAXIMUM_LINES),
+            Violation::LineTooLong(line, length) =>
+                write!(f, "line {} has {} characters, exceeding the maximum of {}", line, length, MAXIMUM_LINE_LENGTH),
+        }
+    }
+}
+
+/// Render `program` as assembly text, provided it fits in a node of the game
+pub fn render(program: &Program) -> Result<String, Vec<Violation>> {
+    let source = format!("{}", program);
+    let violations = check_limits(&source);
+    if violations.is_empty() {
+        Ok(source)
+    } else {
+        Err(violations)
+    }
+}
+
+/// Checks assembly text against the limits the game puts on a node
+pub fn check_limits(source: &str) -> Vec<Violation> {
+    let mut violations: Vec<Violation> = vec![];
+    let lines: Vec<&str> = source.lines().collect();
+    if lines.len() > MAXIMUM_LINES {
+        violations.push(Violation::TooManyLines(lines.len()));
+    }
+    for (index, line) in lines.iter().enumerate() {
+        let length = line.chars().count();
+        if length > MAXIMUM_LINE_LENGTH {
+            violations.push(Violation::LineTooLong(index + 1, length));
+        }
+    }
+    violations
+}
+
+/// A `ParseError` describes what is wrong with assembly text and where
+#[derive(Debug,PartialEq,Eq,Clone)]
+pub struct ParseError {
+    /// The line of the error, starting at 1
+    pub line: usize,
+    /// The column of the error, starting at 1
+    pub column: usize,
+    /// A description of the error
+    pub message: String,
+}
+
+impl ParseError {
+    fn new(line: usize, column: usize, message: String) -> ParseError {
+        ParseError { line, column, message }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// A word of assembly text together with the column it starts at
+struct Token {
+    column: usize,
+    text: String,
+}
+
+/// Parse assembly text into a `Program`
+pub fn parse(source: &str) -> Result<Program, ParseError> {
+    let mut instructions: Vec<Instruction> = vec![];
+    let mut labels: Vec<String> = vec![];
+    let mut jumps: Vec<(String, usize, usize)> = vec![];
+
+    for (line_index, line) in source.lines().enumerate() {
+        let line_number = line_index + 1;
+        let code = match line.find('#') {
+            Some(index) => &line[..index],
+            None => line,
+        };
+        let mut tokens: Vec<Token> = tokenize(code);
+
+        if tokens.first().is_some_and(|token| token.text.starts_with('!')) {
+            let token = tokens.remove(0);
+            if token.text.len() > 1 {
+                tokens.insert(0, Token { column: token.column + 1, text: token.text[1..].to_string() });
+            }
+        }
+
+        if tokens.first().is_some_and(|token| token.text.ends_with(':')) {
+            let token = tokens.remove(0);
+            let label = token.text[..token.text.len() - 1].to_string();
+            if label.is_empty() {
+                return Err(ParseError::new(line_number, token.column, String::from("missing label name")));
+            }
+            if labels.contains(&label) {
+                return Err(ParseError::new(line_number, token.column, format!("duplicate label `{}`", label)));
+            }
+            labels.push(label.clone());
+            instructions.push(Instruction::LABEL(label));
+        }
+
+        if !tokens.is_empty() {
+            let instruction = instruction_from(line_number, &tokens)?;
+            if let Some(label) = jump_target(&instruction) {
+                jumps.push((label, line_number, tokens[1].column));
+            }
+            instructions.push(instruction);
+        }
+    }
+
+    for (label, line_number, column) in jumps {
+        if !labels.contains(&label) {
+            return Err(ParseError::new(line_number, column, format!("unknown label `{}`", label)));
+        }
+    }
+
+    Ok(Program(instructions))
+}
+
+/// Split a line of code into uppercase words. Commas and whitespace separate
+/// words, a colon ends a word.
+fn tokenize(code: &str) -> Vec<Token> {
+    let mut tokens: Vec<Token> = vec![];
+    let mut current: Option<Token> = None;
+    for (index, character) in code.chars().enumerate() {
+        if character.is_whitespace() || character == ',' {
+            if let Some(token) = current.take() {
+                tokens.push(token);
+            }
+        } else {
+            let token = current.get_or_insert(Token { column: index + 1, text: String::new() });
+            token.text.extend(character.to_uppercase());
+            if character == ':' {
+                tokens.push(current.take().unwrap());
+            }
+        }
+    }
+    if let Some(token) = current {
+        tokens.push(token);
+    }
+    tokens
+}
+
+fn instruction_from(line: usize, tokens: &[Token]) -> Result<Instruction, ParseError> {
+    let mnemonic = &tokens[0];
+    let operands = &tokens[1..];
+    let expected = match &mnemonic.text[..] {
+        "NOP" | "SWP" | "SAV" | "NEG" => 0,
+        "ADD" | "SUB" | "JRO" | "JMP" | "JEZ" | "JNZ" | "JGZ" | "JLZ" => 1,
+        "MOV" => 2,
+        _ => return Err(ParseError::new(line, mnemonic.column, format!("unknown instruction `{}`", mnemonic.text))),
+    };
+    if operands.len() != expected {
+        let column = operands.get(expected).map_or(mnemonic.column, |token| token.column);
+        return Err(ParseError::new(
+            line,
+            column,
+            format!("`{}` expects {} operand(s), found {}", mnemonic.text, expected, operands.len())));
+    }
+
+    let instruction = match &mnemonic.text[..] {
+        "NOP" => Instruction::NOP,
+        "SWP" => Instruction::SWP,
+        "SAV" => Instruction::SAV,
+        "NEG" => Instruction::NEG,
+        "ADD" => Instruction::ADD(source_from(line, &operands[0])?),
+        "SUB" => Instruction::SUB(source_from(line, &operands[0])?),
+        "JRO" => Instruction::JRO(source_from(line, &operands[0])?),
+        "JMP" => Instruction::JMP(operands[0].text.clone()),
+        "JEZ" => Instruction::JEZ(operands[0].text.clone()),
+        "JNZ" => Instruction::JNZ(operands[0].text.clone()),
+        "JGZ" => Instruction::JGZ(operands[0].text.clone()),
+        "JLZ" => Instruction::JLZ(operands[0].text.clone()),
+        _ => Instruction::MOV(source_from(line, &operands[0])?, destination_from(line, &operands[1])?),
+    };
+    Ok(instruction)
+}
+
+fn source_from(line: usize, token: &Token) -> Result<Source, ParseError> {
+    if let Some(register) = register_from(&token.text) {
+        return Ok(Source::Register(register));
+    }
+    if let Some(direction) = direction_from(&token.text) {
+        return Ok(Source::Port(direction));
+    }
+    token.text.parse::<i32>()
+        .map(Source::Literal)
+        .map_err(|_| ParseError::new(line, token.column, format!("invalid source `{}`", token.text)))
+}
+
+fn destination_from(line: usize, token: &Token) -> Result<Destination, ParseError> {
+    if let Some(register) = register_from(&token.text) {
+        return Ok(Destination::Register(register));
+    }
+    direction_from(&token.text)
+        .map(Destination::Port)
+        .ok_or_else(|| ParseError::new(line, token.column, format!("invalid destination `{}`", token.text)))
+}
+
+fn register_from(text: &str) -> Option<Register> {
+    match text {
+        "NIL" => Some(Register::NIL),
+        "ACC" => Some(Register::ACC),
+        _ => None,
+    }
+}
+
+fn direction_from(text: &str) -> Option<Direction> {
+    match text {
+        "UP" => Some(Direction::UP),
+        "DOWN" => Some(Direction::DOWN),
+        "LEFT" => Some(Direction::LEFT),
+        "RIGHT" => Some(Direction::RIGHT),
+        "ANY" => Some(Direction::ANY),
+        "LAST" => Some(Direction::LAST),
+        _ => None,
+    }
+}
+
+fn jump_target(instruction: &Instruction) -> Option<String> {
+    match *instruction {
+        Instruction::JMP(ref label) |
+        Instruction::JEZ(ref label) |
+        Instruction::JNZ(ref label) |
+        Instruction::JGZ(ref label) |
+        Instruction::JLZ(ref label) => Some(label.clone()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::TIS_100::{Program, Instruction, Source, Destination, Register};
+    use super::super::TIS_100::Ports::Direction;
+    use super::*;
+
+    #[test]
+    fn should_parse_program_with_labels_comments_and_breakpoints() {
+        let source = "
+# double the input
+start: mov up acc
+  !ADD ACC
+loop:
+jez start
+  MOV ACC,DOWN # output
+jro -1
+";
+
+        assert_eq!(Ok(Program(vec![
+            Instruction::LABEL(String::from("START")),
+            Instruction::MOV(Source::Port(Direction::UP), Destination::Register(Register::ACC)),
+            Instruction::ADD(Source::Register(Register::ACC)),
+            Instruction::LABEL(String::from("LOOP")),
+            Instruction::JEZ(String::from("START")),
+            Instruction::MOV(Source::Register(Register::ACC), Destination::Port(Direction::DOWN)),
+            Instruction::JRO(Source::Literal(-1)),
+        ])), parse(source));
+    }
+
+    #[test]
+    fn should_report_unknown_instruction_with_position() {
+        assert_eq!(
+            Err(ParseError::new(2, 3, String::from("unknown instruction `MUL`"))),
+            parse("NOP\n  MUL 2"));
+    }
+
+    #[test]
+    fn should_report_invalid_operands_with_position() {
+        assert_eq!(
+            Err(ParseError::new(1, 9, String::from("invalid destination `5`"))),
+            parse("MOV UP, 5"));
+        assert_eq!(
+            Err(ParseError::new(1, 9, String::from("`ADD` expects 1 operand(s), found 2"))),
+            parse("ADD ACC ACC"));
+    }
+
+    #[test]
+    fn should_render_program_as_assembly() {
+        let program: Program = Program(vec![
+            Instruction::LABEL(String::from("L")),
+            Instruction::MOV(Source::Port(Direction::UP), Destination::Register(Register::ACC)),
+            Instruction::LABEL(String::from("LONGER")),
+            Instruction::MOV(Source::Register(Register::ACC), Destination::Port(Direction::DOWN)),
+            Instruction::JRO(Source::Literal(-2)),
+        ]);
+
+        assert_eq!(Ok(String::from("L: MOV UP, ACC\nLONGER:\nMOV ACC, DOWN\nJRO -2")), render(&program));
+    }
+
+    #[test]
+    fn should_round_trip_rendered_programs() {
+        let source = "START: MOV UP, ACC\nJGZ POSITIVE\nNEG\nPOSITIVE:\nMOV ACC, RIGHT\nSWP\nSAV\nSUB 1\nJMP START";
+
+        let program: Program = parse(source).unwrap();
+
+        assert_eq!(Ok(String::from(source)), render(&program));
+        assert_eq!(Ok(program.clone()), parse(&render(&program).unwrap()));
+    }
+
+    #[test]
+    fn should_report_violations_of_the_limits() {
+        let program: Program = Program(vec![Instruction::NOP; 16]);
+
+        assert_eq!(Err(vec![Violation::TooManyLines(16)]), render(&program));
+        assert_eq!(vec![Violation::LineTooLong(2, 19)], check_limits("NOP\nMOV -999, LAST # xy"));
+    }
+
+    #[test]
+    fn should_report_unknown_labels() {
+        assert_eq!(
+            Err(ParseError::new(2, 5, String::from("unknown label `END`"))),
+            parse("START:\nJMP END"));
+    }
+}
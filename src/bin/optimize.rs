@@ -1,45 +1,96 @@
-extern crate yaml_rust;
 extern crate tis_100_superoptimizer;
 
 use std::env;
 use std::fs::File;
 use std::io::Read;
-use yaml_rust::YamlLoader;
+use std::path::PathBuf;
 
-use tis_100_superoptimizer::TIS_100::Node;
-use tis_100_superoptimizer::TIS_100::Ports::Port;
-use tis_100_superoptimizer::optimizer::{Config, optimize};
+use tis_100_superoptimizer::checkpoint::Checkpoint;
+use tis_100_superoptimizer::optimizer::{Config, Report, optimize_puzzle_with_report};
+use tis_100_superoptimizer::puzzle::Puzzle;
+use tis_100_superoptimizer::puzzles::find;
 
-fn to_vec(yaml_doc: Option<&Vec<yaml_rust::Yaml>>) -> Vec<i32> {
-    let mut result: Vec<i32> = vec![];
-    for value in yaml_doc.unwrap() {
-        result.push(value.as_i64().unwrap() as i32);
+const SEED: u64 = 100;
+const GENERATED_TEST_CASES: usize = 3;
+
+const USAGE: &str = "usage: optimize [--checkpoint PATH [--resume]] (FILE | --puzzle NAME)";
+
+/// Read the puzzle the arguments name, reduced to a single node
+fn read_puzzle(arguments: &[String]) -> Result<Puzzle, String> {
+    match arguments {
+        [flag, name] if flag == "--puzzle" => {
+            let definition = find(name).ok_or_else(|| format!("unknown puzzle {}", name))?;
+            lone_node(definition.generated(SEED, GENERATED_TEST_CASES), definition.name)
+        },
+        [path] => {
+            let mut data = String::new();
+            let mut f = File::open(path).map_err(|error| error.to_string())?;
+            f.read_to_string(&mut data).map_err(|error| error.to_string())?;
+            lone_node(Puzzle::parse(&data)?, path)
+        },
+        _ => Err(String::from(USAGE)),
     }
-    result
+}
+
+fn lone_node(puzzle: Puzzle, name: &str) -> Result<Puzzle, String> {
+    if puzzle.width() * puzzle.height() > 1 {
+        eprintln!("warning: {} has {} tiles, searching a single node that reads its input and writes its output", name, puzzle.width() * puzzle.height());
+    }
+    puzzle.single_node()
+        .ok_or_else(|| format!("{} has more than one input or output stream", name))
 }
 
 fn main() {
-    let arguments: Vec<String> = env::args().collect();
-    if arguments.len() != 2 {
-        println!("provide a file to optimize");
+    let mut arguments: Vec<String> = env::args().skip(1).collect();
+    let resume = arguments.iter().any(|argument| argument == "--resume");
+    arguments.retain(|argument| argument != "--resume");
+    let checkpoint_path: Option<PathBuf> = match arguments.iter().position(|argument| argument == "--checkpoint") {
+        Some(index) if index + 1 < arguments.len() => {
+            let path = PathBuf::from(arguments.remove(index + 1));
+            arguments.remove(index);
+            Some(path)
+        },
+        _ => None,
+    };
+    if arguments.iter().any(|argument| argument == "--checkpoint") || (resume && checkpoint_path.is_none()) {
+        println!("{}", USAGE);
         return;
     }
 
-    let mut data = String::new();
-    let mut f = File::open(&arguments[1]).unwrap();
-    f.read_to_string(&mut data).unwrap();
-
-    let docs = YamlLoader::load_from_str(&data[..]).unwrap();
-    let doc = &docs[0];
+    let puzzle = match read_puzzle(&arguments) {
+        Ok(found) => found,
+        Err(message) => {
+            println!("{}", message);
+            return;
+        }
+    };
+    let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+    let mut config: Config = Config::new(puzzle.maximum_cycle, puzzle.maximum_program_length)
+        .with_threads(threads);
 
-    let node: Node = Node::new().set_up(Port::new(to_vec(doc["input"].as_vec())));
-    let expected_output: Vec<i32> = to_vec(doc["output"].as_vec());
-    let config: Config = Config::new(
-        doc["maximum_cycle"].as_i64().unwrap() as u32,
-        doc["maximum_program_length"].as_i64().unwrap() as usize);
+    if let Some(ref checkpoint_path) = checkpoint_path {
+        config = config.with_checkpoint(checkpoint_path);
+        if resume {
+            match Checkpoint::read(checkpoint_path) {
+                Ok(checkpoint) => config = config.resuming(&checkpoint),
+                Err(error) => {
+                    println!("could not resume from {}: {}", checkpoint_path.display(), error);
+                    return;
+                },
+            }
+        }
+    }
 
-    match optimize(node, expected_output, config) {
-        Some(program) => println!("{:?}", program),
+    let report: Report = match optimize_puzzle_with_report(&puzzle, config) {
+        Ok(report) => report,
+        Err(message) => {
+            println!("{}", message);
+            return;
+        }
+    };
+    match report.solution {
+        Some(ref program) => println!("{}", program),
         _ => println!("Could not find a program within the bounds"),
     }
+    println!("pruned {} of {} candidates ({:.1}%)", report.pruned, report.examined, report.pruned_percentage());
 }
@@ -1,49 +1,502 @@
 //! The check module verifies if a certain `Program` when run on a specific
-//! `Node` produces the expected result. I.e. Reads the input on `Source::Port`
-//! and writes the correct sequence the `Destination::Port`.
+//! `Node` produces the expected result. I.e. Reads the input on a `Source::Port`
+//! and writes the correct sequence to the `Destination::Port(Direction::DOWN)`.
+//!
+//! `evaluate` tells why a `Program` fails with a `Verdict`, `check` only tells
+//! whether it passes.
+//!
+//! A `Puzzle` is checked by the same rule as a `Node`: a test case passes when
+//! the grid runs until every node is stuck and the output streams then hold
+//! exactly their expected values. It fails on the first wrong value, on extra
+//! values or when it runs out of cycles.
 
-use super::TIS_100::{Node, Program, Cycle};
+use std::fmt::{Display, Formatter, Error};
+use super::TIS_100::{Node, Program, Cycle, ErrorStatus, MAXIMUM_VALUE};
+use super::TIS_100::Executors::{Executor, Halt};
+use super::TIS_100::Bytecodes::{Bytecode, Evaluator};
+use super::TIS_100::Grids::Grid;
+use super::TIS_100::Images::Image;
+use super::puzzle::{Puzzle, TestCase, Expected};
+use super::generator::Generator;
 
-/// Checks if `Node` when run with `Program` writes `expected_result` to `Destination::Port`
+/// The `Verdict` on a run of a `Program`
+#[derive(Debug,PartialEq,Eq,Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum Verdict {
+    /// The expected result was written
+    Pass,
+    /// The value written at `index` is not the expected value
+    WrongOutput {
+        /// The position in the output stream
+        index: usize,
+        /// The expected value
+        expected: i32,
+        /// The value that was written
+        actual: i32,
+    },
+    /// The run ended before the expected value at `index` was written
+    MissingOutput {
+        /// The position in the output stream
+        index: usize,
+        /// The expected value
+        expected: i32,
+    },
+    /// A value was written after the expected result was complete
+    TooMuchOutput {
+        /// The position in the output stream
+        index: usize,
+        /// The value that was written
+        actual: i32,
+    },
+    /// The run deadlocked, with the state of the `Node` when it did
+    Deadlock(Node),
+    /// The run did not finish within the maximum number of cycles
+    Timeout,
+    /// A jump targets a label the program does not define
+    UnknownLabel(String),
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match *self {
+            Verdict::Pass => write!(f, "pass"),
+            Verdict::WrongOutput { index, expected, actual } =>
+                write!(f, "wrong output at {}: expected {}, got {}", index, expected, actual),
+            Verdict::MissingOutput { index, expected } =>
+                write!(f, "missing output at {}: expected {}", index, expected),
+            Verdict::TooMuchOutput { index, actual } =>
+                write!(f, "too much output at {}: got {}", index, actual),
+            Verdict::Deadlock(ref node) => write!(f, "deadlock in {:?} mode with ACC {}", node.mode, node.acc),
+            Verdict::Timeout => write!(f, "timeout"),
+            Verdict::UnknownLabel(ref label) => write!(f, "unknown label {}", label),
+        }
+    }
+}
+
+/// The `Outcome` of running a `Program`: the `Verdict` and the cycles used
+#[derive(Debug,PartialEq,Eq,Clone)]
+pub struct Outcome {
+    /// Why the run passed or failed
+    pub verdict: Verdict,
+    /// The number of cycles the run used
+    pub cycles: u32,
+}
+
+/// Runs `Program` on `Node` and judges what it writes to `Destination::Port(Direction::DOWN)` against `expected_result`.
+/// The run is aborted as soon as a wrong value, or a value too many, is written.
+pub fn evaluate(node: Node, program: Program, expected_result: &[i32], maximum_cycle: u32) -> Outcome {
+    let mut checked = 0;
+    let on_track = |running: &Node| {
+        let output = &running.down.output;
+        while checked < output.len() {
+            if checked >= expected_result.len() || output[checked] != expected_result[checked] {
+                return false;
+            }
+            checked += 1;
+        }
+        true
+    };
+    let loaded: Node = node.load(program);
+    let (result_node, failure) = match loaded.run_while(Cycle::Maximum(maximum_cycle), on_track) {
+        Ok(result_node) => (result_node, None),
+        Err(ErrorStatus::Deadlock(result_node)) => (result_node.clone(), Some(Verdict::Deadlock(result_node))),
+        Err(ErrorStatus::Timeout(result_node)) => (result_node, Some(Verdict::Timeout)),
+        Err(ErrorStatus::UnknownLabel(label)) => (loaded, Some(Verdict::UnknownLabel(label))),
+    };
+    let output = &result_node.down.output;
+    let mismatch = output.iter()
+        .zip(expected_result.iter())
+        .position(|(actual, expected)| actual != expected);
+
+    let verdict = match mismatch {
+        Some(index) => Verdict::WrongOutput { index, expected: expected_result[index], actual: output[index] },
+        None if output.len() > expected_result.len() => {
+            let index = expected_result.len();
+            Verdict::TooMuchOutput { index, actual: output[index] }
+        },
+        None => match failure {
+            Some(verdict) => verdict,
+            None if output.len() < expected_result.len() => {
+                let index = output.len();
+                Verdict::MissingOutput { index, expected: expected_result[index] }
+            },
+            None => Verdict::Pass,
+        },
+    };
+    Outcome { verdict, cycles: result_node.cycles }
+}
+
+/// Checks if `Node` when run with `Program` writes `expected_result` to `Destination::Port(Direction::DOWN)`.
+/// Agrees with `evaluate`, but runs the program in place with an `Executor`.
 pub fn check(node: Node, program: Program, expected_result: Vec<i32>, maximum_cycle: u32) -> bool {
+    check_with_buffer(&node, &program, &expected_result, maximum_cycle, &mut vec![])
+}
+
+/// Checks like `check`, writing the output into `buffer`. Reusing the buffer
+/// for many programs avoids an allocation for every check.
+pub fn check_with_buffer(node: &Node, program: &Program, expected_result: &[i32], maximum_cycle: u32, buffer: &mut Vec<i32>) -> bool {
+    let Program(ref instructions) = *program;
+    let existing = node.down.output.len();
+    if existing > expected_result.len() || node.down.output[..] != expected_result[..existing] {
+        return false;
+    }
+    let expected = &expected_result[existing..];
+    buffer.clear();
+    buffer.resize(expected.len(), 0);
+    let mut executor = Executor::new(node, instructions, buffer);
+
+    let halt = executor.run(Cycle::Maximum(maximum_cycle), |running| {
+        let output = running.output();
+        output.last().is_none_or(|&value| value == expected[output.len() - 1])
+    });
+    halt == Halt::Finished && executor.output().len() == expected.len()
+}
+
+/// Checks like `check` if `Node` when run with `Bytecode` writes `expected_result`. Compile a
+/// `Program` once with `Bytecode::compile` to check it against several expected results.
+pub fn check_compiled(node: &Node, bytecode: &Bytecode, expected_result: &[i32], maximum_cycle: u32) -> bool {
+    check_compiled_with_buffer(node, bytecode, expected_result, maximum_cycle, &mut vec![])
+}
+
+/// Checks like `check_compiled`, writing the output into `buffer`, like
+/// `check_with_buffer` does
+pub fn check_compiled_with_buffer(node: &Node, bytecode: &Bytecode, expected_result: &[i32], maximum_cycle: u32, buffer: &mut Vec<i32>) -> bool {
+    let existing = node.down.output.len();
+    if existing > expected_result.len() || node.down.output[..] != expected_result[..existing] {
+        return false;
+    }
+    let expected = &expected_result[existing..];
+    buffer.clear();
+    buffer.resize(expected.len(), 0);
+    let mut evaluator = Evaluator::new(node, bytecode, buffer);
+
+    let halt = evaluator.run(Cycle::Maximum(maximum_cycle), |running| {
+        let output = running.output();
+        output.last().is_none_or(|&value| value == expected[output.len() - 1])
+    });
+    halt == Halt::Finished && evaluator.output().len() == expected.len()
+}
+
+/// The error of a missing or superfluous value, larger than the difference
+/// between any two values
+pub const MISSING_VALUE_ERROR: u32 = 2 * MAXIMUM_VALUE as u32 + 1;
+
+/// How far the output of a program is from the expected result
+#[derive(Debug,PartialEq,Eq,Clone,Copy)]
+pub struct Distance {
+    /// The sum of the differences between the expected and the actual values,
+    /// with `MISSING_VALUE_ERROR` for every missing value. Superfluous output
+    /// adds a single flat `MISSING_VALUE_ERROR`, however many values it has.
+    /// The sum saturates at `u32::MAX`.
+    pub error: u32,
+    /// The number of cycles the program ran
+    pub cycles: u32,
+}
+
+/// Measures how far the output of `Node` when run with `Bytecode` is from
+/// `expected_result`. The error is zero exactly when `check_compiled` passes.
+pub fn distance_compiled(node: &Node, bytecode: &Bytecode, expected_result: &[i32], maximum_cycle: u32) -> Distance {
+    let remaining = expected_result.len().saturating_sub(node.down.output.len());
+    let mut buffer = vec![0; remaining + 1];
+    let mut evaluator = Evaluator::new(node, bytecode, &mut buffer);
+    let halt = evaluator.run(Cycle::Maximum(maximum_cycle), |_| true);
+
+    let output: Vec<i32> = node.down.output.iter().chain(evaluator.output()).cloned().collect();
+    let differences: u32 = expected_result.iter()
+        .enumerate()
+        .map(|(index, expected)| match output.get(index) {
+            Some(&actual) => (i64::from(*expected) - i64::from(actual)).unsigned_abs() as u32,
+            None => MISSING_VALUE_ERROR,
+        })
+        .fold(0, u32::saturating_add);
+    let superfluous = if output.len() > expected_result.len() || halt == Halt::Overflow { MISSING_VALUE_ERROR } else { 0 };
+    let unfinished = if halt == Halt::Finished || halt == Halt::Overflow { 0 } else { 1 };
+    Distance { error: differences.saturating_add(superfluous + unfinished), cycles: evaluator.cycles }
+}
+
+/// Checks if `Node` when run with `Program` draws `expected_image` by writing
+/// to `Destination::Port(Direction::DOWN)`, i.e. when the down port is attached
+/// to a visualization module
+pub fn check_image(node: Node, program: Program, expected_image: &Image, maximum_cycle: u32) -> bool {
     let local_node: Node = node.load(program);
 
     match local_node.run(Cycle::Maximum(maximum_cycle)) {
-        Ok(result_node) => {
-            same(result_node.down.output, expected_result)
-        },
+        Ok(result_node) => Image::new().draw(&result_node.down.output) == *expected_image,
         Err(_) => false,
     }
 }
 
-fn same<T: Eq>(left: Vec<T>, right: Vec<T>) -> bool {
-    if left.len() == right.len() {
-        for index in 0..(left.len()) {
-            if left[index] != right[index] {
-                return false;
+/// Checks if `programs`, loaded into the execution nodes of `puzzle` row by
+/// row, pass every test case of the puzzle
+pub fn check_puzzle(puzzle: &Puzzle, programs: &[Program]) -> bool {
+    puzzle.tests.iter().all(|test_case| passes(puzzle, programs, test_case))
+}
+
+/// Checks if `programs` pass the test cases of `puzzle` as well as `count`
+/// test cases created by `generator` from `seed`
+pub fn check_generated(puzzle: &Puzzle, programs: &[Program], generator: &Generator, seed: u64, count: usize) -> bool {
+    check_puzzle(puzzle, programs) &&
+        generator.test_cases(seed, count).iter().all(|test_case| passes(puzzle, programs, test_case))
+}
+
+fn passes(puzzle: &Puzzle, programs: &[Program], test_case: &TestCase) -> bool {
+    let mut grid: Grid = puzzle.grid(test_case).load_programs(programs);
+
+    loop {
+        let mut complete = true;
+        for (definition, expected) in puzzle.outputs.iter().zip(test_case.outputs.iter()) {
+            match *expected {
+                Expected::Values(ref values) => {
+                    let actual = grid.output(definition.column).cloned().unwrap_or_default();
+                    if actual.len() > values.len() || actual[..] != values[..actual.len()] {
+                        return false;
+                    }
+                    complete = complete && actual.len() == values.len();
+                },
+                Expected::Image(ref image) => {
+                    complete = complete && grid.image(definition.column).as_ref() == Some(image);
+                },
             }
         }
-        true
-    } else {
-        false
+        match grid.advance() {
+            Some(next_grid) if next_grid.cycles <= puzzle.maximum_cycle => grid = next_grid,
+            Some(_) => return false,
+            None => return complete,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::super::TIS_100::{Node, Program, Instruction, Source, Destination, Register};
-    use super::super::TIS_100::Ports::Port;
+    use super::super::TIS_100::Ports::{Port,Direction};
+    use super::super::puzzle::Puzzle;
     use super::*;
 
+    const DOUBLER: &str = "
+maximum_cycle: 50
+layout:
+  - \"EE\"
+  - \"XE\"
+inputs:
+  - name: IN
+    column: 0
+outputs:
+  - name: OUT
+    column: 1
+tests:
+  - inputs:
+      IN: [1, 2]
+    outputs:
+      OUT: [2, 4]
+  - inputs:
+      IN: [0, 5, 3]
+    outputs:
+      OUT: [0, 10, 6]
+";
+
     #[test]
     fn should_correctly_check_program() {
         let program: Program = Program(vec![
-            Instruction::MOV(Source::Port, Destination::Register(Register::ACC)),
-            Instruction::ADD(Source::Port),
-            Instruction::MOV(Source::Register(Register::ACC), Destination::Port),
+            Instruction::MOV(Source::Port(Direction::UP), Destination::Register(Register::ACC)),
+            Instruction::ADD(Source::Port(Direction::UP)),
+            Instruction::MOV(Source::Register(Register::ACC), Destination::Port(Direction::DOWN)),
         ]);
         let node: Node = Node::new().set_up(Port::new(vec![0, 1, 2, 3]));
 
         assert!(check(node, program, vec![1, 5], 10))
     }
+
+    #[test]
+    fn should_check_programs_with_a_reused_buffer() {
+        let node: Node = Node::new().set_up(Port::new(vec![1, 2, 3]));
+        let echo: Program = Program(vec![
+            Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::DOWN)),
+        ]);
+        let mut buffer: Vec<i32> = vec![];
+
+        assert!(check_with_buffer(&node, &echo, &[1, 2, 3], 20, &mut buffer));
+        assert!(!check_with_buffer(&node, &echo, &[1, 2], 20, &mut buffer));
+        assert!(check_compiled_with_buffer(&node, &Bytecode::compile(&echo), &[1, 2, 3], 20, &mut buffer));
+        assert!(buffer.capacity() >= 3);
+    }
+
+    #[test]
+    fn should_check_compiled_program_like_program() {
+        let node: Node = Node::new().set_up(Port::new(vec![0, 1, 2, 3]));
+        let program: Program = Program(vec![
+            Instruction::MOV(Source::Port(Direction::UP), Destination::Register(Register::ACC)),
+            Instruction::ADD(Source::Port(Direction::UP)),
+            Instruction::MOV(Source::Register(Register::ACC), Destination::Port(Direction::DOWN)),
+        ]);
+        let bytecode: Bytecode = Bytecode::compile(&program);
+
+        for expected in [vec![1, 5], vec![1], vec![1, 5, 0], vec![2, 5]] {
+            assert_eq!(check(node.clone(), program.clone(), expected.clone(), 10), check_compiled(&node, &bytecode, &expected, 10));
+        }
+        assert!(!check_compiled(&node, &bytecode, &[1, 5], 5));
+    }
+
+    #[test]
+    fn should_report_why_a_program_fails() {
+        let node: Node = Node::new().set_up(Port::new(vec![1, 2, 3]));
+        let echo: Program = Program(vec![
+            Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::DOWN)),
+        ]);
+        let stuck: Program = Program(vec![Instruction::JMP(String::from("NOWHERE"))]);
+
+        assert_eq!(Verdict::Pass, evaluate(node.clone(), echo.clone(), &[1, 2, 3], 20).verdict);
+        assert_eq!(Verdict::WrongOutput { index: 1, expected: 5, actual: 2 }, evaluate(node.clone(), echo.clone(), &[1, 5, 3], 20).verdict);
+        assert_eq!(Verdict::TooMuchOutput { index: 2, actual: 3 }, evaluate(node.clone(), echo.clone(), &[1, 2], 20).verdict);
+        assert_eq!(Verdict::MissingOutput { index: 3, expected: 4 }, evaluate(node.clone(), echo.clone(), &[1, 2, 3, 4], 20).verdict);
+        assert_eq!(Verdict::Timeout, evaluate(node.clone(), echo, &[1, 2, 3], 3).verdict);
+        assert_eq!(Verdict::UnknownLabel(String::from("NOWHERE")), evaluate(node, stuck, &[], 20).verdict);
+    }
+
+    #[test]
+    fn should_reject_an_unknown_label_that_is_never_jumped_to() {
+        let node: Node = Node::new().set_up(Port::new(vec![1, 2, 3]));
+        let program: Program = Program(vec![
+            Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::DOWN)),
+            Instruction::JNZ(String::from("MISSING")),
+        ]);
+
+        assert_eq!(Verdict::UnknownLabel(String::from("MISSING")), evaluate(node.clone(), program.clone(), &[1, 2, 3], 20).verdict);
+        assert!(!check(node.clone(), program.clone(), vec![1, 2, 3], 20));
+        assert!(!check_compiled(&node, &Bytecode::compile(&program), &[1, 2, 3], 20));
+    }
+
+    #[test]
+    fn should_stop_at_the_first_wrong_output() {
+        let node: Node = Node::new().set_up(Port::new(vec![1, 2, 3]));
+        let echo: Program = Program(vec![
+            Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::DOWN)),
+        ]);
+
+        assert_eq!(Outcome { verdict: Verdict::WrongOutput { index: 0, expected: 7, actual: 1 }, cycles: 2 }, evaluate(node.clone(), echo.clone(), &[7, 2, 3], 20));
+        assert_eq!(Outcome { verdict: Verdict::TooMuchOutput { index: 1, actual: 2 }, cycles: 4 }, evaluate(node, echo, &[1], 20));
+    }
+
+    #[test]
+    fn should_report_cycles_used() {
+        let node: Node = Node::new().set_up(Port::new(vec![1, 2]));
+        let echo: Program = Program(vec![
+            Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::DOWN)),
+        ]);
+
+        assert_eq!(Outcome { verdict: Verdict::Pass, cycles: 4 }, evaluate(node, echo, &[1, 2], 20));
+    }
+
+    #[test]
+    fn should_correctly_check_image_program() {
+        let program: Program = Program(vec![
+            Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::DOWN)),
+        ]);
+        let node: Node = Node::new().set_up(Port::new(vec![1, 0, 3, 4, -1]));
+        let expected_image: Image = Image::from_rows(vec![vec![0, 3, 4]]);
+
+        assert!(check_image(node.clone(), program.clone(), &expected_image, 20));
+        assert!(!check_image(node, program, &Image::new(), 20));
+    }
+
+    #[test]
+    fn should_correctly_check_puzzle() {
+        let puzzle: Puzzle = Puzzle::parse(DOUBLER).unwrap();
+        let forward: Program = Program(vec![
+            Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::RIGHT)),
+        ]);
+        let double: Program = Program(vec![
+            Instruction::MOV(Source::Port(Direction::LEFT), Destination::Register(Register::ACC)),
+            Instruction::ADD(Source::Register(Register::ACC)),
+            Instruction::MOV(Source::Register(Register::ACC), Destination::Port(Direction::DOWN)),
+        ]);
+        let down: Program = Program(vec![
+            Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::DOWN)),
+        ]);
+
+        assert!(check_puzzle(&puzzle, &[forward.clone(), double, down.clone()]));
+        assert!(!check_puzzle(&puzzle, &[forward.clone(), down.clone(), down]));
+        assert!(!check_puzzle(&puzzle, &[forward]));
+    }
+
+    #[test]
+    fn puzzle_should_pass_by_the_same_rule_as_a_node() {
+        let puzzle: Puzzle = Puzzle::parse("
+maximum_cycle: 50
+layout:
+  - \"E\"
+inputs:
+  - name: IN
+    column: 0
+outputs:
+  - name: OUT
+    column: 0
+tests:
+  - inputs:
+      IN: [3]
+    outputs:
+      OUT: [3]
+").unwrap();
+        let (node, expected) = puzzle.node_cases().unwrap().remove(0);
+        let echo: Program = Program(vec![
+            Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::DOWN)),
+        ]);
+        let repeat: Program = Program(vec![
+            Instruction::MOV(Source::Port(Direction::UP), Destination::Register(Register::ACC)),
+            Instruction::MOV(Source::Register(Register::ACC), Destination::Port(Direction::DOWN)),
+            Instruction::MOV(Source::Register(Register::ACC), Destination::Port(Direction::DOWN)),
+        ]);
+        let spin: Program = Program(vec![
+            Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::DOWN)),
+            Instruction::JRO(Source::Literal(0)),
+        ]);
+
+        for program in &[echo, repeat, spin] {
+            assert_eq!(
+                check(node.clone(), program.clone(), expected.clone(), puzzle.maximum_cycle),
+                check_puzzle(&puzzle, std::slice::from_ref(program)),
+                "{}", program);
+        }
+    }
+
+    #[test]
+    fn should_check_generated_test_cases() {
+        let puzzle: Puzzle = Puzzle::parse(DOUBLER).unwrap();
+        let generator: Generator = Generator::new(vec![(0, 99)], |inputs| vec![inputs[0].iter().map(|value| 2 * value).collect()]);
+        let generous: Puzzle = Puzzle { maximum_cycle: 1000, .. puzzle };
+        let programs: Vec<Program> = vec![
+            Program(vec![Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::RIGHT))]),
+            Program(vec![
+                Instruction::MOV(Source::Port(Direction::LEFT), Destination::Register(Register::ACC)),
+                Instruction::ADD(Source::Register(Register::ACC)),
+                Instruction::MOV(Source::Register(Register::ACC), Destination::Port(Direction::DOWN)),
+            ]),
+            Program(vec![Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::DOWN))]),
+        ];
+        let broken: Generator = Generator::new(vec![(0, 99)], |inputs| vec![inputs[0].clone()]);
+
+        assert!(check_generated(&generous, &programs, &generator, 5, 3));
+        assert!(!check_generated(&generous, &programs, &broken, 5, 3));
+    }
+
+    #[test]
+    fn distance_should_measure_the_error_of_the_output() {
+        let node: Node = Node::new().set_up(Port::new(vec![1, 2, 3]));
+        let echo = Bytecode::compile(&Program(vec![
+            Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::DOWN)),
+        ]));
+
+        let written: Node = node.set_down(Port { input: vec![], output: vec![0] });
+
+        assert_eq!(0, distance_compiled(&node, &echo, &[1, 2, 3], 20).error);
+        assert_eq!(3, distance_compiled(&node, &echo, &[1, 4, 4], 20).error);
+        assert_eq!(MISSING_VALUE_ERROR, distance_compiled(&node, &echo, &[1, 2, 3, 4], 20).error);
+        assert_eq!(MISSING_VALUE_ERROR, distance_compiled(&node, &echo, &[1, 2], 20).error);
+        assert_eq!(MISSING_VALUE_ERROR, distance_compiled(&node, &echo, &[1], 20).error);
+        assert_eq!(0, distance_compiled(&written, &echo, &[0, 1, 2, 3], 20).error);
+        assert_eq!(MISSING_VALUE_ERROR, distance_compiled(&written, &echo, &[0, 1, 2], 20).error);
+        assert_eq!(6, distance_compiled(&node, &echo, &[1, 2, 3], 20).cycles);
+        assert_eq!(2_147_483_649, distance_compiled(&node, &echo, &[i32::MIN, 2, 3], 20).error);
+        assert_eq!(u32::MAX, distance_compiled(&node, &echo, &[i32::MAX, i32::MIN, 3], 20).error);
+    }
 }
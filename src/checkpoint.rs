@@ -0,0 +1,146 @@
+//! The `checkpoint` module saves the progress of a search, so that a long
+//! search can continue where it stopped.
+//!
+//! A checkpoint file records a fingerprint of the search, the position in
+//! the search order from which the search continues and, once the search
+//! found one, the program that solves the problem. The fingerprint tells
+//! whether a checkpoint belongs to a search at all. Only a search in
+//! position order can resume from a checkpoint.
+//!
+//! ```text
+//! search 00c0ffee00c0ffee
+//! position 1024
+//! solution
+//! MOV UP, DOWN
+//! ```
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate tis_100_superoptimizer;
+//!
+//! use tis_100_superoptimizer::checkpoint::Checkpoint;
+//!
+//! fn main() {
+//!     let checkpoint: Checkpoint = Checkpoint::parse("search 00c0ffee00c0ffee\nposition 1024\n").unwrap();
+//!
+//!     assert_eq!(0x00c0_ffee_00c0_ffee, checkpoint.fingerprint);
+//!     assert_eq!(1024, checkpoint.position);
+//!     assert_eq!(None, checkpoint.solution);
+//! }
+//! ```
+
+use std::fmt::{Display, Formatter, Error};
+use std::fs;
+use std::io;
+use std::path::Path;
+use super::TIS_100::Program;
+use super::assembly::parse;
+
+/// A `Checkpoint` records how far a search got
+#[derive(Debug,PartialEq,Eq,Clone)]
+pub struct Checkpoint {
+    /// Identifies the problem, order, alphabet and bounds of the search
+    pub fingerprint: u64,
+    /// The position of the program the search continues with
+    pub position: u64,
+    /// The program that solves the problem, if the search found it
+    pub solution: Option<Program>,
+}
+
+impl Display for Checkpoint {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        writeln!(f, "search {:016x}", self.fingerprint)?;
+        writeln!(f, "position {}", self.position)?;
+        if let Some(ref program) = self.solution {
+            writeln!(f, "solution")?;
+            writeln!(f, "{}", program)?;
+        }
+        Ok(())
+    }
+}
+
+impl Checkpoint {
+    /// Create a `Checkpoint` of the search with `fingerprint` at `position`
+    /// without a solution
+    pub fn new(fingerprint: u64, position: u64) -> Checkpoint {
+        Checkpoint { fingerprint, position, solution: None }
+    }
+
+    /// Parse the text of a checkpoint file
+    pub fn parse(text: &str) -> Result<Checkpoint, String> {
+        let mut lines = text.lines();
+        let fingerprint = lines.next()
+            .and_then(|line| line.strip_prefix("search "))
+            .and_then(|number| u64::from_str_radix(number.trim(), 16).ok())
+            .ok_or_else(|| String::from("expected `search FINGERPRINT` on the first line"))?;
+        let position = lines.next()
+            .and_then(|line| line.strip_prefix("position "))
+            .and_then(|number| number.trim().parse::<u64>().ok())
+            .ok_or_else(|| String::from("expected `position N` on the second line"))?;
+        let solution = match lines.next() {
+            Some("solution") => {
+                let source: Vec<&str> = lines.collect();
+                Some(parse(&source.join("\n")).map_err(|error| format!("solution {}", error))?)
+            },
+            Some(line) if !line.trim().is_empty() => return Err(format!("unexpected line `{}`", line)),
+            _ => None,
+        };
+        Ok(Checkpoint { fingerprint, position, solution })
+    }
+
+    /// Read a `Checkpoint` from the file at `path`
+    pub fn read(path: &Path) -> io::Result<Checkpoint> {
+        let text = fs::read_to_string(path)?;
+        Checkpoint::parse(&text).map_err(|message| io::Error::new(io::ErrorKind::InvalidData, message))
+    }
+
+    /// Write this `Checkpoint` to the file at `path`. The file is replaced at
+    /// once, so an interrupted write leaves the previous checkpoint intact.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".partial");
+        fs::write(&partial, self.to_string())?;
+        fs::rename(&partial, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::TIS_100::{Program, Instruction, Source, Destination, Register};
+    use super::super::TIS_100::Ports::Direction;
+    use super::*;
+
+    #[test]
+    fn checkpoint_should_be_read_back() {
+        let checkpoint: Checkpoint = Checkpoint {
+            fingerprint: 0xdead_beef,
+            position: 77,
+            solution: Some(Program(vec![
+                Instruction::MOV(Source::Port(Direction::UP), Destination::Register(Register::ACC)),
+                Instruction::MOV(Source::Register(Register::ACC), Destination::Port(Direction::DOWN)),
+            ])),
+        };
+
+        assert_eq!(Ok(checkpoint.clone()), Checkpoint::parse(&checkpoint.to_string()));
+        assert_eq!(Ok(Checkpoint::new(3, 5)), Checkpoint::parse(&Checkpoint::new(3, 5).to_string()));
+    }
+
+    #[test]
+    fn checkpoint_should_reject_other_files() {
+        assert!(Checkpoint::parse("MOV UP, DOWN\n").is_err());
+        assert!(Checkpoint::parse("search 1\nposition 3\nbest\n").is_err());
+        assert!(Checkpoint::parse("position 3\n").is_err());
+    }
+
+    #[test]
+    fn checkpoint_should_be_written_to_a_file() {
+        let path = std::env::temp_dir().join(format!("checkpoint-{}.txt", std::process::id()));
+
+        Checkpoint::new(1, 12).write(&path).unwrap();
+        let read = Checkpoint::read(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(Checkpoint::new(1, 12), read.unwrap());
+    }
+}
@@ -0,0 +1,168 @@
+//! The `generator` module creates random test cases the way the game does.
+//!
+//! The game checks a solution against streams of `STREAM_LENGTH` random
+//! values. A `Generator` draws the values of every input stream from a range
+//! and computes the expected output streams with a reference function. The
+//! values come from a seedable `Random`, so the same seed always produces the
+//! same test cases.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate tis_100_superoptimizer;
+//!
+//! use tis_100_superoptimizer::generator::{Generator, STREAM_LENGTH};
+//!
+//! fn double(inputs: &[Vec<i32>]) -> Vec<Vec<i32>> {
+//!     vec![inputs[0].iter().map(|value| 2 * value).collect()]
+//! }
+//!
+//! fn main() {
+//!     let generator: Generator = Generator::new(vec![(10, 99)], double);
+//!     let test_cases = generator.test_cases(42, 3);
+//!
+//!     assert_eq!(3, test_cases.len());
+//!     assert_eq!(STREAM_LENGTH, test_cases[0].inputs[0].len());
+//!     assert_eq!(test_cases, generator.test_cases(42, 3));
+//! }
+//! ```
+
+use super::puzzle::{TestCase, Expected};
+
+/// The number of values in a stream of the game
+pub const STREAM_LENGTH: usize = 39;
+
+/// A `Random` is a small, seedable pseudo random number generator
+#[derive(Debug,PartialEq,Eq,Clone)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    /// Create a `Random` from `seed`
+    pub fn new(seed: u64) -> Random {
+        Random { state: seed }
+    }
+
+    /// The next pseudo random number, advancing self
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A pseudo random number between `minimum` and `maximum`, inclusive.
+    /// Panics when `minimum` exceeds `maximum`.
+    pub fn range(&mut self, minimum: i32, maximum: i32) -> i32 {
+        assert!(minimum <= maximum, "empty range {}..={}", minimum, maximum);
+        let span = (i64::from(maximum) - i64::from(minimum) + 1) as u64;
+        (i64::from(minimum) + (self.next_u64() % span) as i64) as i32
+    }
+
+    /// A pseudo random index below `bound`. Panics when `bound` is zero.
+    pub fn below(&mut self, bound: usize) -> usize {
+        assert!(bound > 0, "no index below zero");
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /// A pseudo random fraction, at least zero and less than one
+    pub fn fraction(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// A `Generator` creates test cases with random input streams
+pub struct Generator {
+    /// The inclusive range of the values of every input stream
+    pub ranges: Vec<(i32, i32)>,
+    /// The number of values of every input stream
+    pub length: usize,
+    /// Computes the output streams from the input streams
+    pub reference: fn(&[Vec<i32>]) -> Vec<Vec<i32>>,
+}
+
+impl Generator {
+    /// Create a `Generator` of streams of `STREAM_LENGTH` values. Panics when
+    /// a range is inverted, i.e. its minimum exceeds its maximum.
+    pub fn new(ranges: Vec<(i32, i32)>, reference: fn(&[Vec<i32>]) -> Vec<Vec<i32>>) -> Generator {
+        if let Some(&(minimum, maximum)) = ranges.iter().find(|&&(minimum, maximum)| minimum > maximum) {
+            panic!("inverted range {}..={}", minimum, maximum);
+        }
+        Generator { ranges, length: STREAM_LENGTH, reference }
+    }
+
+    /// Create a random `TestCase`, advancing `random`
+    pub fn test_case(&self, random: &mut Random) -> TestCase {
+        let inputs: Vec<Vec<i32>> = self.ranges.iter()
+            .map(|&(minimum, maximum)| (0..self.length).map(|_| random.range(minimum, maximum)).collect())
+            .collect();
+        let outputs = (self.reference)(&inputs).into_iter().map(Expected::Values).collect();
+        TestCase { inputs, outputs }
+    }
+
+    /// Create `count` random test cases from `seed`
+    pub fn test_cases(&self, seed: u64, count: usize) -> Vec<TestCase> {
+        let mut random = Random::new(seed);
+        (0..count).map(|_| self.test_case(&mut random)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity(inputs: &[Vec<i32>]) -> Vec<Vec<i32>> {
+        inputs.to_vec()
+    }
+
+    #[test]
+    fn random_should_be_reproducible() {
+        let mut left: Random = Random::new(7);
+        let mut right: Random = Random::new(7);
+
+        for _ in 0..10 {
+            assert_eq!(left.next_u64(), right.next_u64());
+        }
+        assert_ne!(Random::new(7).next_u64(), Random::new(8).next_u64());
+    }
+
+    #[test]
+    fn random_should_stay_within_range() {
+        let mut random: Random = Random::new(1);
+        let values: Vec<i32> = (0..1000).map(|_| random.range(-999, 999)).collect();
+
+        assert!(values.iter().all(|&value| (-999..=999).contains(&value)));
+        assert!(values.iter().any(|&value| value < 0) && values.iter().any(|&value| value > 0));
+        assert!((0..100).all(|_| random.range(3, 3) == 3));
+    }
+
+    #[test]
+    #[should_panic(expected = "no index below zero")]
+    fn random_should_refuse_a_bound_of_zero() {
+        Random::new(1).below(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "empty range 1..=0")]
+    fn random_should_refuse_an_empty_range() {
+        Random::new(1).range(1, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "inverted range 5..=4")]
+    fn generator_should_refuse_an_inverted_range() {
+        Generator::new(vec![(0, 9), (5, 4)], identity);
+    }
+
+    #[test]
+    fn generator_should_compute_expected_output() {
+        let generator: Generator = Generator::new(vec![(0, 1), (-5, 5)], identity);
+
+        let test_case: TestCase = generator.test_cases(3, 1).remove(0);
+
+        assert!(test_case.inputs[0].iter().all(|&value| value == 0 || value == 1));
+        assert_eq!(Expected::Values(test_case.inputs[1].clone()), test_case.outputs[1]);
+    }
+}
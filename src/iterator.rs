@@ -1,10 +1,14 @@
-//! The `optimizer` module contains iterators that allow a user to iterate over
-//! `Program`s in order of increasing content
+//! The `iterator` module contains iterators that allow a user to iterate over
+//! `Program`s.
 //!
-//! The content is a measure of how complex a Program is.
+//! The programs are built from the instructions an `Alphabet` describes. A
+//! `ProgramIterator` counts through the programs, shortest first. A
+//! `CostIterator` yields programs in order of increasing content, or of
+//! increasing cost according to a cost function of the user. The content is a
+//! measure of how complex a Program is.
 
-use std::ops::{Rem, Div};
 use super::TIS_100::{Program, Instruction, Source, Destination, Register};
+use super::TIS_100::Ports::Direction;
 
 /// Allows one to determine the content of certain constructs, e.g. `Program`s
 /// or `Instruction`s
@@ -15,9 +19,9 @@ pub trait Content {
 
 impl Content for Program {
     fn content(&self) -> u32 {
-        let Program(ref instructions) = *self;
+        let Program(instructions) = self;
 
-        instructions.iter().fold(0 as u32, |sum, instruction| sum + instruction.content())
+        instructions.iter().fold(0, |sum, instruction| sum + instruction.content())
     }
 }
 
@@ -30,6 +34,14 @@ impl Content for Instruction {
             Instruction::SAV => 1,
             Instruction::ADD(ref source) => 1 + source.content(),
             Instruction::SUB(ref source) => 1 + source.content(),
+            Instruction::NEG => 1,
+            Instruction::LABEL(_) => 0,
+            Instruction::JMP(_) => 2,
+            Instruction::JEZ(_) => 2,
+            Instruction::JNZ(_) => 2,
+            Instruction::JGZ(_) => 2,
+            Instruction::JLZ(_) => 2,
+            Instruction::JRO(ref source) => 1 + source.content(),
         }
     }
 }
@@ -37,7 +49,7 @@ impl Content for Instruction {
 impl Content for Source {
     fn content(&self) -> u32 {
         match *self {
-            Source::Literal(value) => 1 + (value.abs() as u32),
+            Source::Literal(value) => 1 + value.unsigned_abs(),
             _ => 1,
         }
     }
@@ -49,15 +61,160 @@ impl Content for Destination {
     }
 }
 
-/// Iterator over Programs
+/// The opcodes an `Alphabet` can contain. Jumps to labels are missing because
+/// the iterators do not generate labels.
+#[derive(Debug,PartialEq,Eq,Clone,Copy)]
+pub enum Opcode {
+    /// `NOP`
+    NOP,
+    /// `SWP`
+    SWP,
+    /// `SAV`
+    SAV,
+    /// `NEG`
+    NEG,
+    /// `ADD` from every source
+    ADD,
+    /// `SUB` from every source
+    SUB,
+    /// `MOV` from every source to every destination
+    MOV,
+    /// `JRO` from every source
+    JRO,
+}
+
+/// Describes the instructions programs are built from
+#[derive(Debug,PartialEq,Eq,Clone)]
+pub struct Alphabet {
+    /// The opcodes, in order
+    pub opcodes: Vec<Opcode>,
+    /// The ports and registers instructions read from
+    pub sources: Vec<Source>,
+    /// The ports and registers `MOV` writes to
+    pub destinations: Vec<Destination>,
+    /// The literal values instructions read. `ADD` and `SUB` skip zero,
+    /// because adding zero does nothing. When both `ADD` and `SUB` are
+    /// present, negative literals are folded into their magnitude because
+    /// `SUB n` = `ADD -n`.
+    pub literals: Vec<i32>,
+}
+
+impl Default for Alphabet {
+    fn default() -> Alphabet {
+        Alphabet::new()
+    }
+}
+
+impl Alphabet {
+    /// Create the `Alphabet` of 19 instructions that reads from the up port
+    /// and the accumulator, writes to the down port and the accumulator, and
+    /// knows the literals 0, 1 and -1
+    pub fn new() -> Alphabet {
+        Alphabet {
+            opcodes: vec![Opcode::NOP, Opcode::SWP, Opcode::SAV, Opcode::ADD, Opcode::SUB, Opcode::MOV],
+            sources: vec![Source::Port(Direction::UP), Source::Register(Register::ACC)],
+            destinations: vec![Destination::Port(Direction::DOWN), Destination::Register(Register::ACC)],
+            literals: vec![0, 1, -1],
+        }
+    }
+
+    /// Create an `Alphabet` from self with `opcodes`
+    pub fn with_opcodes(&self, opcodes: &[Opcode]) -> Alphabet {
+        Alphabet { opcodes: opcodes.to_vec(), .. self.clone() }
+    }
+
+    /// Create an `Alphabet` from self with `sources`
+    pub fn with_sources(&self, sources: &[Source]) -> Alphabet {
+        Alphabet { sources: sources.to_vec(), .. self.clone() }
+    }
+
+    /// Create an `Alphabet` from self with `destinations`
+    pub fn with_destinations(&self, destinations: &[Destination]) -> Alphabet {
+        Alphabet { destinations: destinations.to_vec(), .. self.clone() }
+    }
+
+    /// Create an `Alphabet` from self with the `literals`
+    pub fn with_literals(&self, literals: &[i32]) -> Alphabet {
+        Alphabet { literals: literals.to_vec(), .. self.clone() }
+    }
+
+    /// Create an `Alphabet` from self with the literals from `minimum` up to
+    /// and including `maximum`, in order of increasing magnitude
+    pub fn with_literal_range(&self, minimum: i32, maximum: i32) -> Alphabet {
+        let mut literals: Vec<i32> = (minimum..=maximum).collect();
+        literals.sort_by_key(|&value| (value.unsigned_abs(), value < 0));
+        Alphabet { literals, .. self.clone() }
+    }
+
+    /// The instructions of this alphabet, in order
+    pub fn instructions(&self) -> Vec<Instruction> {
+        let sources: Vec<Source> = self.sources.iter().cloned()
+            .chain(self.literals.iter().map(|&value| Source::Literal(value)))
+            .collect();
+        let fold = self.opcodes.contains(&Opcode::ADD) && self.opcodes.contains(&Opcode::SUB);
+        let mut operands: Vec<i32> = vec![];
+        for &value in &self.literals {
+            let operand = if fold { value.unsigned_abs() as i32 } else { value };
+            if operand != 0 && !operands.contains(&operand) {
+                operands.push(operand);
+            }
+        }
+        let arithmetic: Vec<Source> = self.sources.iter().cloned()
+            .chain(operands.into_iter().map(Source::Literal))
+            .collect();
+        let mut instructions: Vec<Instruction> = vec![];
+        for opcode in &self.opcodes {
+            match *opcode {
+                Opcode::NOP => instructions.push(Instruction::NOP),
+                Opcode::SWP => instructions.push(Instruction::SWP),
+                Opcode::SAV => instructions.push(Instruction::SAV),
+                Opcode::NEG => instructions.push(Instruction::NEG),
+                Opcode::ADD => instructions.extend(arithmetic.iter().cloned().map(Instruction::ADD)),
+                Opcode::SUB => instructions.extend(arithmetic.iter().cloned().map(Instruction::SUB)),
+                Opcode::MOV => for destination in &self.destinations {
+                    instructions.extend(sources.iter().map(|source| Instruction::MOV(source.clone(), destination.clone())));
+                },
+                Opcode::JRO => instructions.extend(sources.iter().cloned().map(Instruction::JRO)),
+            }
+        }
+        instructions
+    }
+}
+
+/// Iterator over Programs. It numbers the programs in bijective base of the
+/// number of instructions in its alphabet, so it yields every program once and
+/// shorter programs come first
 pub struct ProgramIterator {
-    current: u32,
+    instructions: Vec<Instruction>,
+    current: u64,
+}
+
+impl Default for ProgramIterator {
+    fn default() -> ProgramIterator {
+        ProgramIterator::new()
+    }
 }
 
 impl ProgramIterator {
     /// Create a `ProgramIterator`
     pub fn new() -> ProgramIterator {
-        ProgramIterator { current: 0 }
+        ProgramIterator::starting_at(0)
+    }
+
+    /// Create a `ProgramIterator` that starts at the program at `position`
+    pub fn starting_at(position: u64) -> ProgramIterator {
+        ProgramIterator::with_alphabet(&Alphabet::new(), position)
+    }
+
+    /// Create a `ProgramIterator` over the programs built from `alphabet` that
+    /// starts at the program at `position`
+    pub fn with_alphabet(alphabet: &Alphabet, position: u64) -> ProgramIterator {
+        ProgramIterator { instructions: alphabet.instructions(), current: position }
+    }
+
+    /// The position of the program this iterator yields next
+    pub fn position(&self) -> u64 {
+        self.current
     }
 }
 
@@ -65,53 +222,130 @@ impl Iterator for ProgramIterator {
     type Item = Program;
 
     fn next(&mut self) -> Option<Program> {
+        if self.instructions.is_empty() {
+            return None;
+        }
         let instructions: Vec<Instruction> =
-            digits_of(self.current, 19)
+            digits_of(self.current, self.instructions.len() as u64)
             .iter()
-            .map(|digit| instruction_map(*digit))
+            .map(|&digit| self.instructions[digit as usize].clone())
             .collect();
-        self.current += 1;
+        match self.current.checked_add(1) {
+            Some(next) => self.current = next,
+            None => self.instructions.clear(),
+        }
         Some(Program(instructions))
     }
 }
 
-fn digits_of(mut n: u32, base: u32) -> Vec<u32> {
-    let mut digits: Vec<u32> = vec![];
-    loop {
-        digits.push(n.rem(base));
-        n = n.div(base);
-        if n == 0 { break; }
+/// Iterator over Programs in order of increasing cost. The cost of a program
+/// is the sum of the costs of its instructions, programs of the same cost come
+/// in lexicographic order of their instructions in the alphabet.
+pub struct CostIterator {
+    alphabet: Vec<Instruction>,
+    costs: Vec<u32>,
+    maximum_length: Option<usize>,
+    budget: u32,
+    indices: Vec<usize>,
+    sum: u32,
+}
+
+impl Default for CostIterator {
+    fn default() -> CostIterator {
+        CostIterator::new()
     }
-    digits
 }
 
-fn instruction_map(index: u32) -> Instruction {
-    match index.rem(19) {
-        0 => Instruction::NOP,
-        1 => Instruction::SWP,
-        2 => Instruction::SAV,
-        3 => Instruction::ADD(Source::Port),
-        4 => Instruction::ADD(Source::Register(Register::ACC)),
-        5 => Instruction::ADD(Source::Literal(1)),
-        6 => Instruction::SUB(Source::Port),
-        7 => Instruction::SUB(Source::Register(Register::ACC)),
-        8 => Instruction::SUB(Source::Literal(1)),
-        9 => Instruction::MOV(Source::Port, Destination::Port),
-        10 => Instruction::MOV(Source::Register(Register::ACC), Destination::Port),
-        11 => Instruction::MOV(Source::Literal(0), Destination::Port),
-        12 => Instruction::MOV(Source::Literal(1), Destination::Port),
-        13 => Instruction::MOV(Source::Literal(-1), Destination::Port),
-        14 => Instruction::MOV(Source::Port, Destination::Register(Register::ACC)),
-        15 => Instruction::MOV(Source::Register(Register::ACC), Destination::Register(Register::ACC)),
-        16 => Instruction::MOV(Source::Literal(0), Destination::Register(Register::ACC)),
-        17 => Instruction::MOV(Source::Literal(1), Destination::Register(Register::ACC)),
-        18 => Instruction::MOV(Source::Literal(-1), Destination::Register(Register::ACC)),
-        _ => Instruction::NOP,
+impl CostIterator {
+    /// Create a `CostIterator` over the programs built from the default
+    /// `Alphabet` in order of increasing `Content`
+    pub fn new() -> CostIterator {
+        CostIterator::with_cost(Alphabet::new().instructions(), |instruction| instruction.content())
+    }
+
+    /// Create a `CostIterator` over programs built from `alphabet` in order of
+    /// increasing `cost`. Every instruction costs at least one.
+    pub fn with_cost<F>(alphabet: Vec<Instruction>, cost: F) -> CostIterator where F: Fn(&Instruction) -> u32 {
+        let costs = alphabet.iter().map(|instruction| cost(instruction).max(1)).collect();
+        CostIterator { alphabet, costs, maximum_length: None, budget: 1, indices: vec![], sum: 0 }
+    }
+
+    /// Create a `CostIterator` from self that ends after all programs of at
+    /// most `maximum_length` instructions
+    pub fn with_maximum_length(self, maximum_length: usize) -> CostIterator {
+        CostIterator { maximum_length: Some(maximum_length), .. self }
+    }
+
+    /// Move to the next sequence of instructions that costs exactly the
+    /// budget, in lexicographic order. Returns false when there is none.
+    fn next_sequence(&mut self) -> bool {
+        let mut from = 0;
+        if let Some(last) = self.indices.pop() {
+            self.sum -= self.costs[last];
+            from = last + 1;
+        }
+        loop {
+            let room = self.maximum_length.is_none_or(|maximum| self.indices.len() < maximum);
+            let fitting = if room {
+                (from..self.costs.len()).find(|&index| self.sum + self.costs[index] <= self.budget)
+            } else {
+                None
+            };
+            match fitting {
+                Some(index) => {
+                    self.indices.push(index);
+                    self.sum += self.costs[index];
+                    if self.sum == self.budget {
+                        return true;
+                    }
+                    from = 0;
+                },
+                None => match self.indices.pop() {
+                    Some(last) => {
+                        self.sum -= self.costs[last];
+                        from = last + 1;
+                    },
+                    None => return false,
+                },
+            }
+        }
     }
 }
 
+impl Iterator for CostIterator {
+    type Item = Program;
+
+    fn next(&mut self) -> Option<Program> {
+        let most_expensive = self.costs.iter().cloned().max()?;
+        while !self.next_sequence() {
+            self.budget += 1;
+            if let Some(maximum) = self.maximum_length {
+                if self.budget > most_expensive * maximum as u32 {
+                    return None;
+                }
+            }
+        }
+        Some(Program(self.indices.iter().map(|&index| self.alphabet[index].clone()).collect()))
+    }
+}
+
+/// The digits of `n + 1` in bijective base `base`, least significant first.
+/// Every sequence of digits is the numeral of exactly one `n`.
+fn digits_of(n: u64, base: u64) -> Vec<u64> {
+    let base = base as u128;
+    let mut digits: Vec<u64> = vec![];
+    let mut rest = n as u128 + 1;
+    while rest > 0 {
+        rest -= 1;
+        digits.push((rest % base) as u64);
+        rest /= base;
+    }
+    digits
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
     use super::super::TIS_100::{Program,Instruction,Source,Destination,Register};
     use super::*;
 
@@ -129,7 +363,128 @@ mod tests {
         ]), programs[18]);
         assert_eq!(Program(vec![
             Instruction::NOP,
-            Instruction::SWP,
+            Instruction::NOP,
         ]), programs[19])
     }
+
+    #[test]
+    fn should_iterate_over_every_program_of_each_length() {
+        let mut counts: Vec<usize> = vec![0; 4];
+        let mut seen: HashSet<String> = HashSet::new();
+        for program in ProgramIterator::new().take(19 + 19 * 19 + 19 * 19 * 19) {
+            let Program(ref instructions) = program;
+            counts[instructions.len()] += 1;
+            seen.insert(format!("{:?}", program));
+        }
+
+        assert_eq!(vec![0, 19, 19 * 19, 19 * 19 * 19], counts);
+        assert_eq!(19 + 19 * 19 + 19 * 19 * 19, seen.len());
+        assert_eq!(Program(vec![Instruction::SWP, Instruction::NOP]), ProgramIterator::starting_at(20).next().unwrap());
+    }
+
+    #[test]
+    fn should_iterate_beyond_the_range_of_u32() {
+        let position: u64 = u32::MAX as u64 + 1;
+        let mut programs = ProgramIterator::starting_at(position);
+
+        assert!(programs.next().is_some());
+        assert_eq!(position + 1, programs.position());
+        assert_eq!(1, ProgramIterator::starting_at(u64::MAX).count());
+    }
+
+    #[test]
+    fn cost_iterator_should_yield_programs_in_order_of_content() {
+        let programs: Vec<Program> = CostIterator::new().take(2000).collect();
+
+        for window in programs.windows(2) {
+            assert!(window[0].content() <= window[1].content());
+        }
+        assert_eq!(Program(vec![Instruction::NOP]), programs[0]);
+        assert_eq!(Program(vec![Instruction::NOP, Instruction::NOP]), programs[3]);
+    }
+
+    #[test]
+    fn cost_iterator_should_use_the_cost_function() {
+        let alphabet = vec![Instruction::NOP, Instruction::NEG];
+        let programs: Vec<Program> = CostIterator::with_cost(alphabet, |instruction| match *instruction {
+            Instruction::NOP => 2,
+            _ => 1,
+        }).take(4).collect();
+
+        assert_eq!(vec![
+            Program(vec![Instruction::NEG]),
+            Program(vec![Instruction::NOP]),
+            Program(vec![Instruction::NEG, Instruction::NEG]),
+            Program(vec![Instruction::NOP, Instruction::NEG]),
+        ], programs);
+    }
+
+    #[test]
+    fn cost_iterator_should_end_after_the_maximum_length() {
+        let programs: Vec<Program> = CostIterator::new().with_maximum_length(2).collect();
+
+        assert_eq!(19 + 19 * 19, programs.len());
+    }
+
+    #[test]
+    fn alphabet_should_describe_the_instructions() {
+        let alphabet: Alphabet = Alphabet::new()
+            .with_opcodes(&[Opcode::NEG, Opcode::ADD, Opcode::MOV])
+            .with_sources(&[])
+            .with_destinations(&[Destination::Register(Register::NIL)])
+            .with_literal_range(-2, 2);
+
+        assert_eq!(vec![0, 1, -1, 2, -2], alphabet.literals);
+        assert_eq!(vec![
+            Instruction::NEG,
+            Instruction::ADD(Source::Literal(1)),
+            Instruction::ADD(Source::Literal(-1)),
+            Instruction::ADD(Source::Literal(2)),
+            Instruction::ADD(Source::Literal(-2)),
+            Instruction::MOV(Source::Literal(0), Destination::Register(Register::NIL)),
+            Instruction::MOV(Source::Literal(1), Destination::Register(Register::NIL)),
+            Instruction::MOV(Source::Literal(-1), Destination::Register(Register::NIL)),
+            Instruction::MOV(Source::Literal(2), Destination::Register(Register::NIL)),
+            Instruction::MOV(Source::Literal(-2), Destination::Register(Register::NIL)),
+        ], alphabet.instructions());
+    }
+
+    #[test]
+    fn alphabet_should_add_and_subtract_negative_literals() {
+        let alphabet: Alphabet = Alphabet::new()
+            .with_opcodes(&[Opcode::ADD, Opcode::SUB])
+            .with_sources(&[])
+            .with_literals(&[-7, 7, -3]);
+
+        assert_eq!(vec![
+            Instruction::ADD(Source::Literal(7)),
+            Instruction::ADD(Source::Literal(3)),
+            Instruction::SUB(Source::Literal(7)),
+            Instruction::SUB(Source::Literal(3)),
+        ], alphabet.instructions());
+    }
+
+    #[test]
+    fn alphabet_should_keep_negative_literals_without_sub() {
+        let alphabet: Alphabet = Alphabet::new()
+            .with_opcodes(&[Opcode::ADD])
+            .with_sources(&[])
+            .with_literal_range(-5, 5);
+
+        assert_eq!(10, alphabet.instructions().len());
+        assert!(alphabet.instructions().contains(&Instruction::ADD(Source::Literal(-3))));
+        assert!(!alphabet.instructions().contains(&Instruction::ADD(Source::Literal(0))));
+    }
+
+    #[test]
+    fn should_iterate_over_programs_of_an_alphabet() {
+        let alphabet: Alphabet = Alphabet::new().with_opcodes(&[Opcode::NEG, Opcode::SWP]);
+        let programs: Vec<Program> = ProgramIterator::with_alphabet(&alphabet, 1).take(2).collect();
+
+        assert_eq!(19, Alphabet::new().instructions().len());
+        assert_eq!(vec![
+            Program(vec![Instruction::SWP]),
+            Program(vec![Instruction::NEG, Instruction::NEG]),
+        ], programs);
+    }
 }
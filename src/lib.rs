@@ -23,9 +23,19 @@
 //! > Superoptimization is the task of finding the optimal code sequence for a
 //! > single, loop-free sequence of instructions
 
+extern crate yaml_rust;
+
 #[allow(non_snake_case)]
 pub mod TIS_100;
+pub mod assembly;
 pub mod check;
+pub mod checkpoint;
+pub mod generator;
 pub mod iterator;
 pub mod optimizer;
+pub mod pruning;
+pub mod puzzle;
+pub mod puzzles;
+pub mod save;
+pub mod stochastic;
 
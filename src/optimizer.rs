@@ -1,36 +1,319 @@
 //! Will find the shortest program to problem
 
-use super::TIS_100::{Node,Program};
-use super::iterator::{ProgramIterator};
-use super::check::check;
+use super::TIS_100::{Node,Program,Instruction};
+use super::iterator::{ProgramIterator, CostIterator, Alphabet, Content};
+use super::TIS_100::Bytecodes::Bytecode;
+use super::check::check_compiled_with_buffer;
+use super::puzzle::Puzzle;
+use super::checkpoint::Checkpoint;
+use super::pruning::Pruner;
+use std::path::{Path, PathBuf};
+
+/// The number of consecutive programs a worker thread checks before the
+/// workers compare their findings
+const SHARD_SIZE: u32 = 1024;
+
+/// The order in which the search tries programs
+#[derive(Debug,Clone,Copy)]
+pub enum Order {
+    /// The order of `ProgramIterator`, shortest programs first
+    Position,
+    /// Increasing `Content`, so the first program found has minimal content
+    Content,
+    /// Increasing cost, where the cost of a program is the sum of the costs
+    /// of its instructions
+    Cost(fn(&Instruction) -> u32),
+}
+
+/// The number of programs checked between two writes of the checkpoint file
+pub const CHECKPOINT_INTERVAL: u64 = 1 << 20;
 
 /// Configuration for the optimize function
+#[derive(Debug,Clone)]
 pub struct Config {
-    /// The maximum allowed number of cycles per program
+    /// The maximum allowed number of cycles per program, counted like a
+    /// `Node` counts them with `Instruction::cycles` until the program waits
+    /// for input that will never arrive. `optimize` and `optimize_puzzle`
+    /// both bound programs by this measure.
     pub maximum_cycle: u32,
     /// The maximum allowed program length
     pub maximum_program_length: usize,
+    /// The number of worker threads that search in parallel
+    pub threads: usize,
+    /// The checkpoint of an earlier run of the same search to continue from
+    pub resume: Option<Checkpoint>,
+    /// The file to record the progress of the search in, if any
+    pub checkpoint: Option<PathBuf>,
+    /// The order in which programs are tried
+    pub order: Order,
+    /// The instructions programs are built from
+    pub alphabet: Alphabet,
+    /// Whether to skip programs that are equivalent to cheaper ones
+    pub prune: bool,
 }
 
 impl Config {
     /// create a `Config` with prescribed maximum_cycle and maximum_program_length
+    /// that searches on a single thread
     pub fn new(maximum_cycle: u32, maximum_program_length: usize) -> Config {
         Config {
-            maximum_cycle: maximum_cycle,
-            maximum_program_length: maximum_program_length,
+            maximum_cycle,
+            maximum_program_length,
+            threads: 1,
+            resume: None,
+            checkpoint: None,
+            order: Order::Position,
+            alphabet: Alphabet::new(),
+            prune: true,
+        }
+    }
+
+    /// Create a `Config` from self that searches with `threads` worker threads
+    pub fn with_threads(&self, threads: usize) -> Config {
+        Config { threads, .. self.clone() }
+    }
+
+    /// Create a `Config` from self that tries programs in `order`
+    pub fn ordered_by(&self, order: Order) -> Config {
+        Config { order, .. self.clone() }
+    }
+
+    /// Create a `Config` from self that builds programs from `alphabet`
+    pub fn with_alphabet(&self, alphabet: &Alphabet) -> Config {
+        Config { alphabet: alphabet.clone(), .. self.clone() }
+    }
+
+    /// Create a `Config` from self that does or does not `prune` programs
+    /// equivalent to cheaper ones
+    pub fn with_pruning(&self, prune: bool) -> Config {
+        Config { prune, .. self.clone() }
+    }
+
+    /// Create a `Config` from self that records its progress in the file at
+    /// `path` every `CHECKPOINT_INTERVAL` programs
+    pub fn with_checkpoint(&self, path: &Path) -> Config {
+        Config { checkpoint: Some(path.to_path_buf()), .. self.clone() }
+    }
+
+    /// Create a `Config` from self that continues the search recorded in
+    /// `checkpoint`. The search fails when the checkpoint was written by a
+    /// search for another problem, order, alphabet or bounds. Only a search
+    /// in `Order::Position` can resume, as it starts at any position at once.
+    /// The other orders would have to enumerate every program before it.
+    pub fn resuming(&self, checkpoint: &Checkpoint) -> Config {
+        Config { resume: Some(checkpoint.clone()), .. self.clone() }
+    }
+}
+
+/// The result of a search
+#[derive(Debug,PartialEq,Eq,Clone)]
+pub struct Report {
+    /// The program found, if any
+    pub solution: Option<Program>,
+    /// The number of programs the search took from the enumeration
+    pub examined: u64,
+    /// The number of examined programs that were pruned instead of checked
+    pub pruned: u64,
+}
+
+impl Report {
+    /// The percentage of the examined programs that was pruned
+    pub fn pruned_percentage(&self) -> f64 {
+        if self.examined == 0 {
+            0.0
+        } else {
+            100.0 * self.pruned as f64 / self.examined as f64
         }
     }
 }
 
 /// Tries to find a `Program` that satisfies the context
 pub fn optimize(node: Node, expected_output: Vec<i32>, config: Config) -> Option<Program> {
-    for program in ProgramIterator::new() {
-        if check(node.clone(), program.clone(), expected_output.clone(), config.maximum_cycle) {
-            return Some(program.clone());
+    optimize_with_report(node, expected_output, config).ok().and_then(|report| report.solution)
+}
+
+/// Like `optimize`, but reports how much of the search was pruned. Fails
+/// when the checkpoint file can not be written or belongs to another search.
+pub fn optimize_with_report(node: Node, expected_output: Vec<i32>, config: Config) -> Result<Report, String> {
+    let problem = format!("{:?} {:?}", node, expected_output);
+    search(&config, &problem, |program, buffer| {
+        check_compiled_with_buffer(&node, &Bytecode::compile(program), &expected_output, config.maximum_cycle, buffer)
+    })
+}
+
+/// Tries to find a `Program` for a puzzle that is a lone execution node
+/// which passes all of its test cases, see `Puzzle::node_cases`. Other
+/// puzzles have no such program.
+pub fn optimize_puzzle(puzzle: &Puzzle, config: Config) -> Option<Program> {
+    optimize_puzzle_with_report(puzzle, config).ok().and_then(|report| report.solution)
+}
+
+/// Like `optimize_puzzle`, but reports how much of the search was pruned. Every
+/// candidate is compiled once and checked against all test cases like
+/// `optimize` checks it. Fails without searching when the puzzle is not a
+/// lone execution node, see `Puzzle::single_node` to reduce it to one, and
+/// fails when the checkpoint file can not be written or belongs to another
+/// search.
+pub fn optimize_puzzle_with_report(puzzle: &Puzzle, config: Config) -> Result<Report, String> {
+    let cases = puzzle.node_cases().ok_or_else(|| String::from("puzzle is not a single node"))?;
+    search(&config, &format!("{:?}", puzzle), |program, buffer| {
+        let bytecode = Bytecode::compile(program);
+        cases.iter().all(|(node, expected)| check_compiled_with_buffer(node, &bytecode, expected, config.maximum_cycle, buffer))
+    })
+}
+
+/// What a worker found in its shard of the programs
+enum Finding {
+    /// The program at this position passes
+    Solution(u64, Program),
+    /// The program at this position is too long, as are all that follow
+    Exhausted(u64),
+}
+
+impl Finding {
+    fn position(&self) -> u64 {
+        match *self {
+            Finding::Solution(position, _) | Finding::Exhausted(position) => position,
+        }
+    }
+}
+
+/// Returns the first program in the order of `Config::order`, from the
+/// position of `Config::resume` on, that `passes`. Every round each worker
+/// takes the next shard of `SHARD_SIZE` programs that are not pruned. The
+/// earliest finding of a round decides, so the result does not depend on the
+/// number of threads. The search of `problem` refuses a checkpoint of another
+/// search, and failing to write the checkpoint file stops it with its error.
+/// `passes` gets an output buffer to reuse, one for every shard.
+fn search<F>(config: &Config, problem: &str, passes: F) -> Result<Report, String> where F: Fn(&Program, &mut Vec<i32>) -> bool + Sync {
+    let fingerprint = fingerprint(problem, config);
+    let mut start: u64 = match config.resume {
+        Some(_) if !matches!(config.order, Order::Position) => {
+            return Err(String::from("only a search in position order can resume"));
+        },
+        Some(ref checkpoint) if checkpoint.fingerprint != fingerprint => {
+            return Err(String::from("the checkpoint belongs to another search"));
+        },
+        Some(Checkpoint { solution: Some(ref program), .. }) => {
+            return Ok(Report { solution: Some(program.clone()), examined: 0, pruned: 0 });
+        },
+        Some(ref checkpoint) => checkpoint.position,
+        None => 0,
+    };
+    let round_size = config.threads.max(1) * SHARD_SIZE as usize;
+    let mut programs = programs(config, start).zip(start..);
+    let mut pruner = Pruner::new(cost_function(config.order));
+    let report = |pruner: &Pruner, examined: u64, solution: Option<Program>| Ok(Report { solution, examined, pruned: pruner.pruned });
+    let mut examined: u64 = 0;
+    loop {
+        let mut round: Vec<(Program, u64)> = Vec::with_capacity(round_size);
+        let mut next = start;
+        for (program, position) in programs.by_ref() {
+            next = position + 1;
+            examined += 1;
+            let candidate = length(&program) > config.maximum_program_length || !config.prune || !pruner.prunes(&program);
+            if candidate {
+                round.push((program, position));
+                if round.len() == round_size {
+                    break;
+                }
+            }
+        }
+        let findings: Vec<Finding> = if round.len() <= SHARD_SIZE as usize {
+            search_shard(&round, config.maximum_program_length, &passes).into_iter().collect()
+        } else {
+            std::thread::scope(|scope| {
+                let workers: Vec<_> = round.chunks(SHARD_SIZE as usize)
+                    .map(|shard| {
+                        let passes = &passes;
+                        scope.spawn(move || search_shard(shard, config.maximum_program_length, passes))
+                    })
+                    .collect();
+                workers.into_iter().filter_map(|worker| worker.join().unwrap()).collect()
+            })
+        };
+
+        match findings.into_iter().min_by_key(Finding::position) {
+            Some(Finding::Solution(position, program)) => {
+                record(config, Checkpoint { fingerprint, position, solution: Some(program.clone()) })?;
+                return report(&pruner, examined, Some(program));
+            },
+            Some(Finding::Exhausted(position)) => {
+                record(config, Checkpoint::new(fingerprint, position))?;
+                return report(&pruner, examined, None);
+            },
+            None if round.len() < round_size => {
+                record(config, Checkpoint::new(fingerprint, next))?;
+                return report(&pruner, examined, None);
+            },
+            None => {
+                if start / CHECKPOINT_INTERVAL != next / CHECKPOINT_INTERVAL {
+                    record(config, Checkpoint::new(fingerprint, next))?;
+                }
+                start = next;
+            },
         }
-        if length(&program) > config.maximum_program_length {break;}
     }
-    None
+}
+
+/// The cost of an instruction in `order`, which the pruner needs to know
+/// which programs the search tried before
+fn cost_function(order: Order) -> fn(&Instruction) -> u32 {
+    match order {
+        Order::Position => |_| 1,
+        Order::Content => |instruction| instruction.content(),
+        Order::Cost(cost) => cost,
+    }
+}
+
+/// Identifies the search of `problem` with `config`: a hash of the problem,
+/// the order, the alphabet and the bounds on programs. The FNV-1a hash is
+/// the same for every build, so checkpoints outlive the binary.
+fn fingerprint(problem: &str, config: &Config) -> u64 {
+    let order = match config.order {
+        Order::Position => "position",
+        Order::Content => "content",
+        Order::Cost(_) => "cost",
+    };
+    let search = format!("{}\n{}\n{:?}\n{} {}", problem, order, config.alphabet, config.maximum_program_length, config.maximum_cycle);
+    search.bytes().fold(0xcbf2_9ce4_8422_2325, |hash: u64, byte| (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// The programs to search in the order of `config`, from `start` on. Only
+/// `Order::Position` starts anywhere else than at the beginning.
+fn programs(config: &Config, start: u64) -> Box<dyn Iterator<Item = Program>> {
+    let by_cost = |iterator: CostIterator| -> Box<dyn Iterator<Item = Program>> {
+        Box::new(iterator.with_maximum_length(config.maximum_program_length))
+    };
+    match config.order {
+        Order::Position => Box::new(ProgramIterator::with_alphabet(&config.alphabet, start)),
+        Order::Content => by_cost(CostIterator::with_cost(config.alphabet.instructions(), |instruction| instruction.content())),
+        Order::Cost(cost) => by_cost(CostIterator::with_cost(config.alphabet.instructions(), cost)),
+    }
+}
+
+fn record(config: &Config, checkpoint: Checkpoint) -> Result<(), String> {
+    match config.checkpoint {
+        Some(ref path) => checkpoint.write(path)
+            .map_err(|error| format!("could not write checkpoint {}: {}", path.display(), error)),
+        None => Ok(()),
+    }
+}
+
+/// Checks the `programs` of a shard with their positions, stopping at the
+/// first program that passes or that is too long
+fn search_shard<F>(programs: &[(Program, u64)], maximum_program_length: usize, passes: &F) -> Option<Finding> where F: Fn(&Program, &mut Vec<i32>) -> bool {
+    let mut buffer: Vec<i32> = vec![];
+    programs.iter()
+        .find_map(|&(ref program, position)| {
+            if length(program) > maximum_program_length {
+                Some(Finding::Exhausted(position))
+            } else if passes(program, &mut buffer) {
+                Some(Finding::Solution(position, program.clone()))
+            } else {
+                None
+            }
+        })
 }
 
 fn length(program: &Program) -> usize {
@@ -40,23 +323,190 @@ fn length(program: &Program) -> usize {
 
 #[cfg(test)]
 mod tests {
-    use super::super::TIS_100::{Node, Program, Instruction, Source, Destination};
-    use super::super::TIS_100::Ports::Port;
+    use super::super::TIS_100::{Node, Program, Instruction, Source, Destination, Register};
+    use super::super::TIS_100::Ports::{Port,Direction};
+    use super::super::iterator::Opcode;
+    use super::super::check::check;
     use super::*;
 
     #[test]
     fn should_find_simple_program_to_output_zero() {
         let node: Node = Node::new().set_up(Port::new(vec![0, 1, 2, 3]));
         let expected_output: Vec<i32> = vec![0, 0, 0, 0];
-        let config: Config = Config::new(10,3);
+        let config: Config = Config::new(20,3);
 
         match optimize(node, expected_output, config) {
             Some(program) => assert_eq!(Program(vec![
-                Instruction::MOV(Source::Literal(0), Destination::Port),
-                Instruction::ADD(Source::Port),
+                Instruction::MOV(Source::Literal(0), Destination::Port(Direction::DOWN)),
+                Instruction::ADD(Source::Port(Direction::UP)),
             ]), program),
-            _ => assert!(false)
+            _ => panic!()
         }
 
     }
+
+    #[test]
+    fn should_find_program_for_puzzle() {
+        let puzzle: Puzzle = Puzzle::parse("
+input: [3, 1, 2]
+output: [3, 1, 2]
+").unwrap();
+
+        match optimize_puzzle(&puzzle, Config::new(20, 3)) {
+            Some(program) => assert_eq!(Program(vec![
+                Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::DOWN)),
+            ]), program),
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn should_refuse_a_puzzle_that_is_not_a_single_node() {
+        let puzzle: Puzzle = Puzzle::parse("
+layout:
+  - \"EE\"
+inputs:
+  - name: IN
+    column: 0
+outputs:
+  - name: OUT
+    column: 1
+tests:
+  - inputs:
+      IN: [3, 1, 2]
+    outputs:
+      OUT: [3, 1, 2]
+").unwrap();
+
+        assert_eq!(Err(String::from("puzzle is not a single node")), optimize_puzzle_with_report(&puzzle, Config::new(20, 3)));
+        assert!(optimize_puzzle_with_report(&puzzle.single_node().unwrap(), Config::new(20, 3)).unwrap().solution.is_some());
+    }
+
+    #[test]
+    fn should_bound_cycles_alike_for_nodes_and_puzzles() {
+        let puzzle: Puzzle = Puzzle::parse("
+input: [3, 1, 2]
+output: [6, 2, 4]
+").unwrap();
+        let node: Node = Node::new().set_up(Port::new(vec![3, 1, 2]));
+        let expected_output: Vec<i32> = vec![6, 2, 4];
+
+        for maximum_cycle in 8..14 {
+            let config: Config = Config::new(maximum_cycle, 4);
+            assert_eq!(
+                optimize(node.clone(), expected_output.clone(), config.clone()),
+                optimize_puzzle(&puzzle, config));
+        }
+        assert!(optimize_puzzle(&puzzle, Config::new(12, 4)).is_some());
+    }
+
+    #[test]
+    fn should_find_the_same_program_with_several_threads() {
+        let node: Node = Node::new().set_up(Port::new(vec![3, 1, 2]));
+        let expected_output: Vec<i32> = vec![4, 2, 3];
+        let config: Config = Config::new(30, 3);
+
+        let sequential = optimize(node.clone(), expected_output.clone(), config.clone());
+
+        assert!(sequential.is_some());
+        assert_eq!(sequential, optimize(node.clone(), expected_output.clone(), config.with_threads(4)));
+        assert_eq!(None, optimize(node, vec![7, 7, 7, 7], Config::new(30, 1).with_threads(3)));
+    }
+
+    #[test]
+    fn should_resume_search_from_checkpoint() {
+        let node: Node = Node::new().set_up(Port::new(vec![0, 1, 2, 3]));
+        let expected_output: Vec<i32> = vec![0, 0, 0, 0];
+        let path = std::env::temp_dir().join(format!("optimizer-{}.checkpoint", std::process::id()));
+        let config: Config = Config::new(20, 3).with_checkpoint(&path);
+
+        let found = optimize(node.clone(), expected_output.clone(), config.clone());
+        let checkpoint = Checkpoint::read(&path).unwrap();
+        let skipped = optimize(node.clone(), expected_output.clone(), config.resuming(&Checkpoint::new(checkpoint.fingerprint, checkpoint.position + 1)));
+        let finished = optimize(node.clone(), expected_output.clone(), config.resuming(&checkpoint));
+        std::fs::remove_file(&path).unwrap();
+        let other = optimize_with_report(node, vec![1, 1, 1, 1], config.resuming(&checkpoint));
+
+        assert_eq!(found, checkpoint.solution);
+        assert!(skipped.is_some());
+        assert_ne!(found, skipped);
+        assert_eq!(found, finished);
+        assert_eq!(Err(String::from("the checkpoint belongs to another search")), other);
+    }
+
+    #[test]
+    fn should_resume_only_in_position_order() {
+        let node: Node = Node::new().set_up(Port::new(vec![0, 1, 2, 3]));
+        let config: Config = Config::new(20, 3).ordered_by(Order::Content).resuming(&Checkpoint::new(0, 5));
+
+        assert_eq!(Err(String::from("only a search in position order can resume")), optimize_with_report(node, vec![0, 0, 0, 0], config));
+    }
+
+    #[test]
+    fn should_report_a_checkpoint_that_can_not_be_written() {
+        let node: Node = Node::new().set_up(Port::new(vec![0, 1, 2, 3]));
+        let path = std::env::temp_dir().join("missing-directory").join("optimizer.checkpoint");
+        let config: Config = Config::new(20, 3).with_checkpoint(&path);
+
+        let result = optimize_with_report(node, vec![0, 0, 0, 0], config);
+
+        assert!(result.unwrap_err().starts_with("could not write checkpoint"));
+    }
+
+    #[test]
+    fn should_find_program_with_minimal_content() {
+        let node: Node = Node::new().set_up(Port::new(vec![0, 0, 0]));
+        let expected_output: Vec<i32> = vec![1, 1, 1];
+        let config: Config = Config::new(30, 3).ordered_by(Order::Content);
+        let passes = |program: &Program| check(node.clone(), program.clone(), expected_output.clone(), config.maximum_cycle);
+
+        let simplest = optimize(node.clone(), expected_output.clone(), config.clone()).unwrap();
+
+        assert_eq!(Program(vec![
+            Instruction::ADD(Source::Port(Direction::UP)),
+            Instruction::MOV(Source::Literal(1), Destination::Port(Direction::DOWN)),
+        ]), simplest);
+        assert!(CostIterator::new().take_while(|program| program.content() < simplest.content()).all(|program| !passes(&program)));
+        assert_eq!(Some(simplest), optimize(node, expected_output, config.with_threads(3)));
+    }
+
+    #[test]
+    fn should_order_by_cost_function() {
+        let node: Node = Node::new().set_up(Port::new(vec![3, 1, 2]));
+        let expected_output: Vec<i32> = vec![4, 2, 3];
+        let config: Config = Config::new(30, 3);
+
+        let shortest = optimize(node.clone(), expected_output.clone(), config.clone()).unwrap();
+        let cheapest = optimize(node, expected_output, config.ordered_by(Order::Cost(|_| 1))).unwrap();
+
+        assert_eq!(length(&shortest), length(&cheapest));
+    }
+
+    #[test]
+    fn should_search_the_alphabet_of_the_config() {
+        let node: Node = Node::new().set_up(Port::new(vec![1, 2, 3]));
+        let expected_output: Vec<i32> = vec![8, 16, 24];
+        let alphabet: Alphabet = Alphabet::new()
+            .with_opcodes(&[Opcode::ADD, Opcode::MOV])
+            .with_sources(&[Source::Port(Direction::UP), Source::Register(Register::ACC)])
+            .with_literals(&[]);
+
+        assert_eq!(None, optimize(node.clone(), vec![100, 100, 100], Config::new(30, 2)));
+        assert!(optimize(node.clone(), vec![100, 100, 100], Config::new(30, 2).with_alphabet(&Alphabet::new().with_literals(&[100]))).is_some());
+        assert!(optimize(node, expected_output, Config::new(60, 5).with_alphabet(&alphabet).ordered_by(Order::Content)).is_some());
+    }
+
+    #[test]
+    fn should_find_the_same_program_when_pruning() {
+        let node: Node = Node::new().set_up(Port::new(vec![3, 1, 2]));
+        let expected_output: Vec<i32> = vec![4, 2, 3];
+        let config: Config = Config::new(30, 3);
+
+        let pruned = optimize_with_report(node.clone(), expected_output.clone(), config.clone()).unwrap();
+        let unpruned = optimize_with_report(node, expected_output, config.with_pruning(false)).unwrap();
+
+        assert_eq!(unpruned.solution, pruned.solution);
+        assert_eq!(0, unpruned.pruned);
+        assert!(pruned.pruned > 0);
+    }
 }
@@ -0,0 +1,241 @@
+//! The `pruning` module rejects candidate programs that are equivalent to a
+//! cheaper program.
+//!
+//! A run of instructions that neither reads from nor writes to a port only
+//! changes the registers. The `Pruner` follows what such a run does to `ACC`
+//! and `BAC` symbolically. When a run of a candidate has the same effect as a
+//! cheaper run that is no longer, replacing the one by the other gives an
+//! equivalent program that the search already tried. As every instruction of
+//! such a run takes a single cycle, that program also fits the bounds on
+//! length and cycles. This catches instructions that do nothing, like `NOP`
+//! or `MOV ACC, ACC`, runs that cancel, like `SWP` followed by `SWP`, and
+//! values that are overwritten before they are used.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate tis_100_superoptimizer;
+//!
+//! use tis_100_superoptimizer::TIS_100::{Program, Instruction};
+//! use tis_100_superoptimizer::pruning::Pruner;
+//!
+//! fn main() {
+//!     let mut pruner: Pruner = Pruner::new(|_| 1);
+//!
+//!     assert!(pruner.prunes(&Program(vec![Instruction::SWP, Instruction::SWP, Instruction::NEG])));
+//!     assert_eq!(1, pruner.pruned);
+//! }
+//! ```
+
+use std::collections::HashMap;
+use super::TIS_100::{Program, Instruction, Source, Destination, Register, saturate};
+
+/// What a register holds in terms of the registers before a run of
+/// instructions
+#[derive(Debug,PartialEq,Eq,Hash,Clone,Copy)]
+enum Value {
+    Acc,
+    NegatedAcc,
+    Bac,
+    NegatedBac,
+    Constant(i32),
+}
+
+impl Value {
+    fn negated(self) -> Value {
+        match self {
+            Value::Acc => Value::NegatedAcc,
+            Value::NegatedAcc => Value::Acc,
+            Value::Bac => Value::NegatedBac,
+            Value::NegatedBac => Value::Bac,
+            Value::Constant(value) => Value::Constant(-value),
+        }
+    }
+}
+
+/// The effect of a run of instructions on `ACC` and `BAC`
+#[derive(Debug,PartialEq,Eq,Hash,Clone,Copy)]
+struct Effect {
+    acc: Value,
+    bac: Value,
+}
+
+/// The effect of running no instructions
+const UNCHANGED: Effect = Effect { acc: Value::Acc, bac: Value::Bac };
+
+impl Effect {
+    /// The effect of following this effect with `instruction`, if the
+    /// instruction only changes the registers in a way `Value` can express
+    fn then(self, instruction: &Instruction) -> Option<Effect> {
+        match *instruction {
+            Instruction::NOP => Some(self),
+            Instruction::SWP => Some(Effect { acc: self.bac, bac: self.acc }),
+            Instruction::SAV => Some(Effect { bac: self.acc, .. self }),
+            Instruction::NEG => Some(Effect { acc: self.acc.negated(), .. self }),
+            Instruction::MOV(ref source, Destination::Register(Register::ACC)) => {
+                self.value_of(source).map(|acc| Effect { acc, .. self })
+            },
+            Instruction::MOV(ref source, Destination::Register(Register::NIL)) => {
+                self.value_of(source).map(|_| self)
+            },
+            Instruction::ADD(ref source) => self.value_of(source).and_then(|value| self.add(value, 1)),
+            Instruction::SUB(ref source) => self.value_of(source).and_then(|value| self.add(value, -1)),
+            _ => None,
+        }
+    }
+
+    fn value_of(self, source: &Source) -> Option<Value> {
+        match *source {
+            Source::Register(Register::ACC) => Some(self.acc),
+            Source::Register(Register::NIL) => Some(Value::Constant(0)),
+            Source::Literal(value) => Some(Value::Constant(saturate(value))),
+            Source::Port(_) => None,
+        }
+    }
+
+    /// Adding zero changes nothing and adding constants is exact, other sums
+    /// may saturate and are not followed
+    fn add(self, value: Value, sign: i32) -> Option<Effect> {
+        match (self.acc, value) {
+            (_, Value::Constant(0)) => Some(self),
+            (Value::Constant(acc), Value::Constant(value)) => {
+                Some(Effect { acc: Value::Constant(saturate(acc + sign * value)), .. self })
+            },
+            _ => None,
+        }
+    }
+}
+
+/// A `Pruner` remembers the cost and length of the cheapest program seen for
+/// every effect, and counts the candidates it examined and pruned
+pub struct Pruner {
+    cost: fn(&Instruction) -> u32,
+    cheapest: HashMap<Effect, (u32, usize)>,
+    /// The number of candidates examined
+    pub examined: u64,
+    /// The number of candidates pruned
+    pub pruned: u64,
+}
+
+impl Pruner {
+    /// Create a `Pruner` for a search that tries programs in order of
+    /// increasing `cost`, where every instruction costs at least one
+    pub fn new(cost: fn(&Instruction) -> u32) -> Pruner {
+        let mut cheapest = HashMap::new();
+        cheapest.insert(UNCHANGED, (0, 0));
+        Pruner { cost, cheapest, examined: 0, pruned: 0 }
+    }
+
+    /// Examine the next candidate of the search. Returns true when it is
+    /// equivalent to a cheaper program that is no longer, which the search
+    /// tried before.
+    pub fn prunes(&mut self, program: &Program) -> bool {
+        self.examined += 1;
+        if self.is_redundant(program) {
+            self.pruned += 1;
+            return true;
+        }
+        let Program(ref instructions) = *program;
+        let whole = instructions.iter().try_fold(UNCHANGED, |effect, instruction| effect.then(instruction));
+        if let Some(effect) = whole {
+            let seen = (self.cost_of(instructions), instructions.len());
+            let cheapest = self.cheapest.entry(effect).or_insert(seen);
+            *cheapest = (*cheapest).min(seen);
+        }
+        false
+    }
+
+    /// Whether a run of instructions in `program` has the effect of a
+    /// cheaper run seen before that has at most as many instructions.
+    /// Relative jumps depend on the length of the program, so programs with a
+    /// `JRO` are never redundant.
+    pub fn is_redundant(&self, program: &Program) -> bool {
+        let Program(ref instructions) = *program;
+        if instructions.iter().any(|instruction| matches!(*instruction, Instruction::JRO(_))) {
+            return false;
+        }
+        (0..instructions.len()).any(|start| {
+            let mut effect = UNCHANGED;
+            let mut cost = 0;
+            for (end, instruction) in instructions.iter().enumerate().skip(start) {
+                match effect.then(instruction) {
+                    Some(next) => effect = next,
+                    None => return false,
+                }
+                cost += (self.cost)(instruction).max(1);
+                let whole = start == 0 && end + 1 == instructions.len();
+                let length = end + 1 - start;
+                match self.cheapest.get(&effect) {
+                    Some(&(cheapest, shortest)) if cheapest < cost && shortest <= length && !(whole && cheapest == 0) => return true,
+                    _ => {},
+                }
+            }
+            false
+        })
+    }
+
+    fn cost_of(&self, instructions: &[Instruction]) -> u32 {
+        instructions.iter().map(|instruction| (self.cost)(instruction).max(1)).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::TIS_100::{Program, Instruction, Source, Destination, Register};
+    use super::super::TIS_100::Ports::Direction;
+    use super::*;
+
+    fn mov(source: Source, destination: Destination) -> Instruction {
+        Instruction::MOV(source, destination)
+    }
+
+    #[test]
+    fn should_prune_instructions_that_do_nothing() {
+        let pruner: Pruner = Pruner::new(|_| 1);
+        let acc = Destination::Register(Register::ACC);
+        let output = mov(Source::Register(Register::ACC), Destination::Port(Direction::DOWN));
+
+        assert!(pruner.is_redundant(&Program(vec![Instruction::NOP, output.clone()])));
+        assert!(pruner.is_redundant(&Program(vec![output.clone(), mov(Source::Register(Register::ACC), acc)])));
+        assert!(pruner.is_redundant(&Program(vec![Instruction::ADD(Source::Literal(0)), output.clone()])));
+        assert!(pruner.is_redundant(&Program(vec![Instruction::NEG, Instruction::NEG, output.clone()])));
+        assert!(!pruner.is_redundant(&Program(vec![Instruction::NEG, output])));
+    }
+
+    #[test]
+    fn should_prune_runs_equivalent_to_cheaper_ones() {
+        let mut pruner: Pruner = Pruner::new(|_| 1);
+        let acc = Destination::Register(Register::ACC);
+        let output = mov(Source::Register(Register::ACC), Destination::Port(Direction::DOWN));
+        let overwritten = Program(vec![mov(Source::Literal(1), acc.clone()), mov(Source::Literal(0), acc.clone()), output.clone()]);
+        let added = Program(vec![mov(Source::Literal(1), acc.clone()), Instruction::ADD(Source::Literal(1)), output]);
+
+        assert!(!pruner.prunes(&overwritten));
+        assert!(!pruner.prunes(&Program(vec![mov(Source::Literal(0), acc.clone())])));
+        assert!(!pruner.prunes(&Program(vec![mov(Source::Literal(2), acc)])));
+        assert!(pruner.prunes(&overwritten));
+        assert!(pruner.prunes(&added));
+        assert_eq!((5, 2), (pruner.examined, pruner.pruned));
+    }
+
+    #[test]
+    fn should_not_prune_runs_whose_cheaper_equivalent_is_longer() {
+        let mut pruner: Pruner = Pruner::new(|instruction| match *instruction {
+            Instruction::MOV(Source::Literal(1), _) => 10,
+            _ => 1,
+        });
+        let acc = Destination::Register(Register::ACC);
+        let output = mov(Source::Register(Register::ACC), Destination::Port(Direction::DOWN));
+
+        assert!(!pruner.prunes(&Program(vec![mov(Source::Literal(0), acc.clone()), Instruction::ADD(Source::Literal(1))])));
+        assert!(!pruner.prunes(&Program(vec![mov(Source::Literal(1), acc.clone()), output.clone()])));
+        assert!(pruner.prunes(&Program(vec![mov(Source::Literal(1), acc.clone()), mov(Source::Literal(0), acc.clone()), Instruction::ADD(Source::Literal(1)), output])));
+    }
+
+    #[test]
+    fn should_not_prune_programs_that_jump_relative() {
+        let pruner: Pruner = Pruner::new(|_| 1);
+
+        assert!(!pruner.is_redundant(&Program(vec![Instruction::NOP, Instruction::JRO(Source::Literal(-1))])));
+    }
+}
@@ -0,0 +1,458 @@
+//! The `puzzle` module describes a TIS-100 puzzle: the layout of the grid, the
+//! streams attached to it and the test cases a solution has to pass.
+//!
+//! A puzzle is written in YAML. The `layout` lists the rows of the grid, one
+//! character per node: `E` for an execution node, `S` for a stack memory node
+//! and `X` for a damaged node. Input streams feed the top row, output streams
+//! collect from the bottom row. Every test case names the values of each
+//! stream. An output stream marked as `image` expects the rows of colors a
+//! visualization module should show.
+//!
+//! ```yaml
+//! name: SIGNAL AMPLIFIER
+//! maximum_cycle: 200
+//! maximum_program_length: 5
+//! layout:
+//!   - "EX"
+//!   - "ES"
+//! inputs:
+//!   - name: IN.A
+//!     column: 0
+//! outputs:
+//!   - name: OUT.A
+//!     column: 0
+//! tests:
+//!   - inputs:
+//!       IN.A: [1, 2, 3]
+//!     outputs:
+//!       OUT.A: [2, 4, 6]
+//! ```
+//!
+//! The original format with a single `input` and `output` is read as a puzzle
+//! with a single execution node.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate tis_100_superoptimizer;
+//!
+//! use tis_100_superoptimizer::puzzle::Puzzle;
+//!
+//! fn main() {
+//!     let puzzle: Puzzle = Puzzle::parse("
+//! maximum_cycle: 10
+//! maximum_program_length: 3
+//! input: [0, 1, 2, 3]
+//! output: [1, 5]
+//! ").unwrap();
+//!
+//!     assert_eq!(1, puzzle.execution_nodes());
+//!     assert_eq!(1, puzzle.tests.len());
+//! }
+//! ```
+
+use std::convert::TryFrom;
+use yaml_rust::{Yaml, YamlLoader};
+use super::TIS_100::Node;
+use super::TIS_100::Ports::Port;
+use super::TIS_100::Grids::Grid;
+use super::TIS_100::Images::Image;
+use super::assembly::MAXIMUM_LINES;
+
+/// The number of cycles a puzzle may take when the spec does not say
+pub const DEFAULT_MAXIMUM_CYCLE: u32 = 100_000;
+
+/// The kind of node at a position of the layout
+#[derive(Debug,PartialEq,Eq,Clone,Copy)]
+pub enum Kind {
+    /// A basic execution node
+    Execution,
+    /// A stack memory node
+    Stack,
+    /// A damaged node
+    Damaged,
+}
+
+impl Kind {
+    /// The `Kind` a character of a layout row stands for: `E`, `S` or `X`
+    pub fn from_char(character: char) -> Option<Kind> {
+        match character.to_ascii_uppercase() {
+            'E' => Some(Kind::Execution),
+            'S' => Some(Kind::Stack),
+            'X' => Some(Kind::Damaged),
+            _ => None,
+        }
+    }
+}
+
+/// A `StreamDefinition` names a stream and places it on a column of the grid
+#[derive(Debug,PartialEq,Eq,Clone)]
+pub struct StreamDefinition {
+    /// The name of the stream, e.g. `IN.A`
+    pub name: String,
+    /// The column of the grid the stream is attached to
+    pub column: usize,
+    /// Whether the stream is drawn by a visualization module
+    pub image: bool,
+}
+
+impl StreamDefinition {
+    /// Create a `StreamDefinition` of a stream of values
+    pub fn new(name: &str, column: usize) -> StreamDefinition {
+        StreamDefinition { name: name.to_string(), column, image: false }
+    }
+}
+
+/// What a test case expects of an output stream
+#[derive(Debug,PartialEq,Eq,Clone)]
+pub enum Expected {
+    /// The exact sequence of values
+    Values(Vec<i32>),
+    /// The image drawn on a visualization module
+    Image(Image),
+}
+
+/// A `TestCase` holds the values of every stream of a puzzle, in the order of
+/// the stream definitions
+#[derive(Debug,PartialEq,Eq,Clone)]
+pub struct TestCase {
+    /// The values of each input stream
+    pub inputs: Vec<Vec<i32>>,
+    /// The expectation of each output stream
+    pub outputs: Vec<Expected>,
+}
+
+/// A `Puzzle` describes a grid, its streams and the test cases to pass
+#[derive(Debug,PartialEq,Eq,Clone)]
+pub struct Puzzle {
+    /// The name of the puzzle
+    pub name: String,
+    /// The kind of every node, row by row
+    pub layout: Vec<Vec<Kind>>,
+    /// The input streams feeding the top row
+    pub inputs: Vec<StreamDefinition>,
+    /// The output streams collecting from the bottom row
+    pub outputs: Vec<StreamDefinition>,
+    /// The test cases a solution has to pass
+    pub tests: Vec<TestCase>,
+    /// The maximum number of cycles a test case may take
+    pub maximum_cycle: u32,
+    /// The maximum length of a program to search for
+    pub maximum_program_length: usize,
+}
+
+impl Puzzle {
+    /// Parse a `Puzzle` from the text of a YAML document
+    pub fn parse(text: &str) -> Result<Puzzle, String> {
+        let docs = YamlLoader::load_from_str(text).map_err(|error| format!("{:?}", error))?;
+        match docs.first() {
+            Some(doc) => Puzzle::from_yaml(doc),
+            None => Err(String::from("empty puzzle description")),
+        }
+    }
+
+    /// Read a `Puzzle` from a YAML document
+    pub fn from_yaml(doc: &Yaml) -> Result<Puzzle, String> {
+        let maximum_cycle = optional_integer(&doc["maximum_cycle"], "maximum_cycle")?
+            .unwrap_or(DEFAULT_MAXIMUM_CYCLE);
+        let maximum_program_length = optional_integer(&doc["maximum_program_length"], "maximum_program_length")?
+            .unwrap_or(MAXIMUM_LINES);
+
+        if doc["tests"].is_badvalue() {
+            return Ok(Puzzle {
+                name: String::new(),
+                layout: vec![vec![Kind::Execution]],
+                inputs: vec![StreamDefinition::new("IN", 0)],
+                outputs: vec![StreamDefinition::new("OUT", 0)],
+                tests: vec![TestCase {
+                    inputs: vec![values(&doc["input"], "input")?],
+                    outputs: vec![Expected::Values(values(&doc["output"], "output")?)],
+                }],
+                maximum_cycle,
+                maximum_program_length,
+            });
+        }
+
+        let layout = layout(&doc["layout"])?;
+        let width = layout[0].len();
+        let inputs = stream_definitions(&doc["inputs"], "inputs", width)?;
+        let outputs = stream_definitions(&doc["outputs"], "outputs", width)?;
+        let tests = match doc["tests"].as_vec() {
+            Some(cases) => cases.iter()
+                .map(|case| test_case(case, &inputs, &outputs))
+                .collect::<Result<Vec<TestCase>, String>>()?,
+            None => return Err(String::from("expected a list of tests")),
+        };
+
+        Ok(Puzzle {
+            name: doc["name"].as_str().unwrap_or("").to_string(),
+            layout,
+            inputs,
+            outputs,
+            tests,
+            maximum_cycle,
+            maximum_program_length,
+        })
+    }
+
+    /// The number of columns of the grid
+    pub fn width(&self) -> usize {
+        self.layout[0].len()
+    }
+
+    /// The number of rows of the grid
+    pub fn height(&self) -> usize {
+        self.layout.len()
+    }
+
+    /// The number of execution nodes, i.e. the number of programs a solution has
+    pub fn execution_nodes(&self) -> usize {
+        self.layout.iter()
+            .map(|row| row.iter().filter(|&&kind| kind == Kind::Execution).count())
+            .sum()
+    }
+
+    /// This puzzle reduced to a single execution node that reads its only
+    /// input stream and writes its only output stream, e.g. to search for the
+    /// computational core of a solution. Puzzles with more streams have no
+    /// such reduction.
+    pub fn single_node(&self) -> Option<Puzzle> {
+        if self.inputs.len() != 1 || self.outputs.len() != 1 {
+            return None;
+        }
+        Some(Puzzle {
+            layout: vec![vec![Kind::Execution]],
+            inputs: vec![StreamDefinition { column: 0, .. self.inputs[0].clone() }],
+            outputs: vec![StreamDefinition { column: 0, .. self.outputs[0].clone() }],
+            .. self.clone()
+        })
+    }
+
+    /// The test cases of a puzzle that is a lone execution node, which reads
+    /// its input from above and writes its output below, as the `Node` with
+    /// that input and the expected output. Other puzzles need a `Grid`.
+    pub fn node_cases(&self) -> Option<Vec<(Node, Vec<i32>)>> {
+        let lone = self.layout == vec![vec![Kind::Execution]] &&
+            self.inputs.len() <= 1 &&
+            self.outputs.len() == 1;
+        if !lone {
+            return None;
+        }
+        self.tests.iter()
+            .map(|test_case| match test_case.outputs[..] {
+                [Expected::Values(ref values)] => {
+                    let input = test_case.inputs.first().cloned().unwrap_or_default();
+                    Some((Node::new().set_up(Port::new(input)), values.clone()))
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Create the `Grid` of this puzzle with the streams of `test_case` attached
+    pub fn grid(&self, test_case: &TestCase) -> Grid {
+        let mut grid = Grid::new(self.width(), self.height());
+        for (row, kinds) in self.layout.iter().enumerate() {
+            for (column, kind) in kinds.iter().enumerate() {
+                grid = match *kind {
+                    Kind::Execution => grid,
+                    Kind::Stack => grid.with_stack(row, column),
+                    Kind::Damaged => grid.with_damaged(row, column),
+                };
+            }
+        }
+        for (definition, values) in self.inputs.iter().zip(test_case.inputs.iter()) {
+            grid = grid.with_input(definition.column, values.clone());
+        }
+        for definition in &self.outputs {
+            grid = grid.with_output(definition.column);
+        }
+        grid
+    }
+}
+
+fn optional_integer<T: TryFrom<i64>>(yaml: &Yaml, field: &str) -> Result<Option<T>, String> {
+    if yaml.is_badvalue() {
+        Ok(None)
+    } else {
+        match yaml.as_i64().map(T::try_from) {
+            Some(Ok(value)) => Ok(Some(value)),
+            _ => Err(format!("expected `{}` to be a non-negative number within range", field)),
+        }
+    }
+}
+
+fn values(yaml: &Yaml, field: &str) -> Result<Vec<i32>, String> {
+    match yaml.as_vec() {
+        Some(entries) => entries.iter()
+            .map(|entry| entry.as_i64()
+                 .and_then(|value| i32::try_from(value).ok())
+                 .ok_or_else(|| format!("expected `{}` to hold numbers within range", field)))
+            .collect(),
+        None => Err(format!("expected `{}` to be a list of numbers", field)),
+    }
+}
+
+fn layout(yaml: &Yaml) -> Result<Vec<Vec<Kind>>, String> {
+    let rows = yaml.as_vec().ok_or_else(|| String::from("expected `layout` to be a list of rows"))?;
+    let mut layout: Vec<Vec<Kind>> = vec![];
+    for row in rows {
+        let kinds = row.as_str()
+            .ok_or_else(|| String::from("expected a row of the layout to be text"))?
+            .chars()
+            .map(|character| Kind::from_char(character).ok_or_else(|| format!("unknown node `{}` in layout", character)))
+            .collect::<Result<Vec<Kind>, String>>()?;
+        if kinds.is_empty() || layout.first().is_some_and(|first: &Vec<Kind>| first.len() != kinds.len()) {
+            return Err(String::from("expected all rows of the layout to have the same, positive length"));
+        }
+        layout.push(kinds);
+    }
+    if layout.is_empty() {
+        Err(String::from("expected at least one row in the layout"))
+    } else {
+        Ok(layout)
+    }
+}
+
+fn stream_definitions(yaml: &Yaml, field: &str, width: usize) -> Result<Vec<StreamDefinition>, String> {
+    if yaml.is_badvalue() {
+        return Ok(vec![]);
+    }
+    let entries = yaml.as_vec().ok_or_else(|| format!("expected `{}` to be a list of streams", field))?;
+    let mut definitions: Vec<StreamDefinition> = vec![];
+    for entry in entries {
+        let name = entry["name"].as_str().ok_or_else(|| format!("expected a name for each of `{}`", field))?;
+        let column = match entry["column"].as_i64() {
+            Some(column) if column >= 0 && (column as usize) < width => column as usize,
+            _ => return Err(format!("expected a column of the grid for stream `{}`", name)),
+        };
+        if definitions.iter().any(|definition| definition.name == name) {
+            return Err(format!("expected one stream named `{}` in `{}`", name, field));
+        }
+        if definitions.iter().any(|definition| definition.column == column) {
+            return Err(format!("expected one stream in column {} of `{}`", column, field));
+        }
+        let image = entry["image"].as_bool().unwrap_or(false);
+        definitions.push(StreamDefinition { name: name.to_string(), column, image });
+    }
+    Ok(definitions)
+}
+
+fn test_case(yaml: &Yaml, inputs: &[StreamDefinition], outputs: &[StreamDefinition]) -> Result<TestCase, String> {
+    let input_values = inputs.iter()
+        .map(|definition| values(&yaml["inputs"][definition.name.as_str()], &definition.name))
+        .collect::<Result<Vec<Vec<i32>>, String>>()?;
+    let expected = outputs.iter()
+        .map(|definition| {
+            let entry = &yaml["outputs"][definition.name.as_str()];
+            if definition.image {
+                let rows = entry.as_vec()
+                    .ok_or_else(|| format!("expected `{}` to be a list of rows", definition.name))?
+                    .iter()
+                    .map(|row| values(row, &definition.name))
+                    .collect::<Result<Vec<Vec<i32>>, String>>()?;
+                Ok(Expected::Image(Image::from_rows(rows)))
+            } else {
+                values(entry, &definition.name).map(Expected::Values)
+            }
+        })
+        .collect::<Result<Vec<Expected>, String>>()?;
+    Ok(TestCase { inputs: input_values, outputs: expected })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::TIS_100::Grids::Tile;
+    use super::*;
+
+    const SPEC: &str = "
+name: DOUBLER
+maximum_cycle: 50
+layout:
+  - \"EX\"
+  - \"ES\"
+inputs:
+  - name: IN.A
+    column: 0
+outputs:
+  - name: OUT.A
+    column: 0
+  - name: OUT.I
+    column: 1
+    image: true
+tests:
+  - inputs:
+      IN.A: [1, 2]
+    outputs:
+      OUT.A: [2, 4]
+      OUT.I:
+        - [0, 3]
+";
+
+    #[test]
+    fn puzzle_should_be_read_from_a_spec() {
+        let puzzle: Puzzle = Puzzle::parse(SPEC).unwrap();
+
+        assert_eq!("DOUBLER", puzzle.name);
+        assert_eq!(vec![vec![Kind::Execution, Kind::Damaged], vec![Kind::Execution, Kind::Stack]], puzzle.layout);
+        assert_eq!(2, puzzle.execution_nodes());
+        assert_eq!(50, puzzle.maximum_cycle);
+        assert_eq!(MAXIMUM_LINES, puzzle.maximum_program_length);
+        assert_eq!(TestCase {
+            inputs: vec![vec![1, 2]],
+            outputs: vec![Expected::Values(vec![2, 4]), Expected::Image(Image::from_rows(vec![vec![0, 3]]))],
+        }, puzzle.tests[0]);
+    }
+
+    #[test]
+    fn puzzle_should_read_the_original_format() {
+        let puzzle: Puzzle = Puzzle::parse("
+maximum_program_length: 3
+maximum_cycle: 10
+input: [0, 1]
+output: [1]
+").unwrap();
+
+        assert_eq!(vec![vec![Kind::Execution]], puzzle.layout);
+        assert_eq!(vec![StreamDefinition::new("IN", 0)], puzzle.inputs);
+        assert_eq!(vec![Expected::Values(vec![1])], puzzle.tests[0].outputs);
+        assert_eq!(3, puzzle.maximum_program_length);
+    }
+
+    #[test]
+    fn puzzle_should_create_the_grid_of_a_test_case() {
+        let puzzle: Puzzle = Puzzle::parse(SPEC).unwrap();
+
+        let grid: Grid = puzzle.grid(&puzzle.tests[0]);
+
+        assert_eq!(Tile::Damaged, grid.tiles[1]);
+        assert!(grid.stack(1, 1).is_some());
+        assert_eq!(vec![1, 2], grid.inputs[0].values);
+        assert_eq!(Some(&vec![]), grid.output(1));
+    }
+
+    #[test]
+    fn puzzle_should_give_node_cases_for_a_lone_node() {
+        let lone: Puzzle = Puzzle::parse("
+input: [0, 1]
+output: [1]
+").unwrap();
+
+        assert_eq!(Some(vec![(Node::new().set_up(Port::new(vec![0, 1])), vec![1])]), lone.node_cases());
+        assert_eq!(None, Puzzle::parse(SPEC).unwrap().node_cases());
+    }
+
+    #[test]
+    fn puzzle_should_report_bad_specs() {
+        assert!(Puzzle::parse("layout: [\"EE\", \"E\"]\ntests: []").is_err());
+        assert!(Puzzle::parse("layout: [\"EQ\"]\ntests: []").is_err());
+        assert!(Puzzle::parse("layout: [\"E\"]\ninputs:\n  - name: IN\n    column: 3\ntests: []").is_err());
+        assert!(Puzzle::parse("maximum_cycle: -1\ninput: []\noutput: []").is_err());
+        assert!(Puzzle::parse("maximum_cycle: 4294967296\ninput: []\noutput: []").is_err());
+        assert!(Puzzle::parse("maximum_cycle: 4294967295\ninput: []\noutput: []").is_ok());
+        assert!(Puzzle::parse("input: [4294967297]\noutput: []").is_err());
+        assert!(Puzzle::parse("layout: [\"EE\"]\ninputs:\n  - name: IN\n    column: 0\n  - name: IN\n    column: 1\ntests: []").is_err());
+        assert!(Puzzle::parse("layout: [\"EE\"]\noutputs:\n  - name: A\n    column: 1\n  - name: B\n    column: 1\ntests: []").is_err());
+        assert!(Puzzle::parse("layout: [\"EE\"]\ninputs:\n  - name: A\n    column: 1\noutputs:\n  - name: B\n    column: 1\ntests: []").is_ok());
+    }
+}
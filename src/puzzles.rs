@@ -0,0 +1,432 @@
+//! The `puzzles` module holds the puzzles of the first segments of the game's
+//! campaign as data.
+//!
+//! Every `Definition` knows its name, segment number, layout and streams, and
+//! has a reference function that computes the expected output streams from
+//! the input streams. A short sample of input values makes each definition a
+//! complete `Puzzle`.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate tis_100_superoptimizer;
+//!
+//! use tis_100_superoptimizer::puzzles::find;
+//!
+//! fn main() {
+//!     let definition = find("SIGNAL_AMPLIFIER").unwrap();
+//!
+//!     assert_eq!(10981, definition.segment);
+//!     assert_eq!(vec![vec![2, -6]], definition.expected(&[vec![1, -3]]));
+//! }
+//! ```
+
+use super::puzzle::{Puzzle, Kind, StreamDefinition, TestCase, Expected};
+use super::assembly::MAXIMUM_LINES;
+use super::generator::{Generator, STREAM_LENGTH};
+
+/// The number of cycles a campaign puzzle may spend per value of its longest
+/// input stream
+pub const CYCLES_PER_VALUE: u32 = 50;
+
+/// A `Definition` describes a puzzle of the campaign
+pub struct Definition {
+    /// The name of the puzzle as shown in the game
+    pub name: &'static str,
+    /// The segment number of the puzzle
+    pub segment: u32,
+    /// The rows of the layout, in the notation of the puzzle spec
+    pub layout: [&'static str; 3],
+    /// The names and columns of the input streams
+    pub inputs: &'static [(&'static str, usize)],
+    /// The names and columns of the output streams
+    pub outputs: &'static [(&'static str, usize)],
+    /// A sample of values for every input stream
+    pub sample: &'static [&'static [i32]],
+    /// The inclusive range of the generated values of every input stream
+    pub ranges: &'static [(i32, i32)],
+    /// Computes the output streams from the input streams
+    pub reference: fn(&[Vec<i32>]) -> Vec<Vec<i32>>,
+}
+
+impl Definition {
+    /// The output streams expected for `inputs`
+    pub fn expected(&self, inputs: &[Vec<i32>]) -> Vec<Vec<i32>> {
+        (self.reference)(inputs)
+    }
+
+    /// The `TestCase` for `inputs` with its expected output
+    pub fn test_case(&self, inputs: Vec<Vec<i32>>) -> TestCase {
+        let outputs = self.expected(&inputs).into_iter().map(Expected::Values).collect();
+        TestCase { inputs, outputs }
+    }
+
+    /// The `Generator` of random test cases for this puzzle
+    pub fn generator(&self) -> Generator {
+        Generator::new(self.ranges.to_vec(), self.reference)
+    }
+
+    /// The `Puzzle` of this definition with a test case for the sample input,
+    /// followed by `count` test cases generated from `seed`
+    pub fn generated(&self, seed: u64, count: usize) -> Puzzle {
+        let puzzle = self.puzzle();
+        let mut tests = puzzle.tests.clone();
+        tests.extend(self.generator().test_cases(seed, count));
+        Puzzle {
+            tests,
+            maximum_cycle: CYCLES_PER_VALUE * STREAM_LENGTH.max(self.longest_sample()) as u32,
+            .. puzzle
+        }
+    }
+
+    /// The `Puzzle` of this definition with a test case for the sample input.
+    /// It may take `CYCLES_PER_VALUE` cycles for every sample value.
+    pub fn puzzle(&self) -> Puzzle {
+        let longest = self.longest_sample();
+        let streams = |definitions: &[(&str, usize)]| -> Vec<StreamDefinition> {
+            definitions.iter().map(|&(name, column)| StreamDefinition::new(name, column)).collect()
+        };
+        Puzzle {
+            name: self.name.to_string(),
+            layout: self.layout.iter()
+                .map(|row| row.chars().filter_map(Kind::from_char).collect())
+                .collect(),
+            inputs: streams(self.inputs),
+            outputs: streams(self.outputs),
+            tests: vec![self.test_case(self.sample.iter().map(|values| values.to_vec()).collect())],
+            maximum_cycle: CYCLES_PER_VALUE * longest as u32,
+            maximum_program_length: MAXIMUM_LINES,
+        }
+    }
+
+    fn longest_sample(&self) -> usize {
+        self.sample.iter().map(|values| values.len()).max().unwrap_or(0)
+    }
+}
+
+/// The puzzles of the first segments of the campaign, from SELF-TEST
+/// DIAGNOSTIC up to SIGNAL MULTIPLIER, in the order of the game. The later
+/// segments are not included.
+pub const CAMPAIGN: &[Definition] = &[
+    Definition {
+        name: "SELF-TEST DIAGNOSTIC",
+        segment: 150,
+        layout: ["EXEE", "EXEE", "EEXE"],
+        inputs: &[("IN.X", 0), ("IN.A", 3)],
+        outputs: &[("OUT.X", 0), ("OUT.A", 3)],
+        sample: &[&[51, 62, 13, 84, 25], &[68, 29, 10, 41, 92]],
+        ranges: &[(10, 99), (10, 99)],
+        reference: self_test_diagnostic,
+    },
+    Definition {
+        name: "SIGNAL AMPLIFIER",
+        segment: 10981,
+        layout: ["EEEX", "EEEE", "XEEE"],
+        inputs: &[("IN.A", 1)],
+        outputs: &[("OUT.A", 2)],
+        sample: &[&[12, 47, 83, 50, 64]],
+        ranges: &[(10, 99)],
+        reference: signal_amplifier,
+    },
+    Definition {
+        name: "DIFFERENTIAL CONVERTER",
+        segment: 20176,
+        layout: ["EEEE", "EEEX", "EEEE"],
+        inputs: &[("IN.A", 1), ("IN.B", 2)],
+        outputs: &[("OUT.P", 1), ("OUT.N", 2)],
+        sample: &[&[34, 18, 72, 40, 19], &[20, 51, 72, 11, 38]],
+        ranges: &[(10, 99), (10, 99)],
+        reference: differential_converter,
+    },
+    Definition {
+        name: "SIGNAL COMPARATOR",
+        segment: 21340,
+        layout: ["EEEE", "EXXX", "EEEE"],
+        inputs: &[("IN", 0)],
+        outputs: &[("OUT.G", 1), ("OUT.E", 2), ("OUT.L", 3)],
+        sample: &[&[2, 0, -1, 1, 0, -2]],
+        ranges: &[(-2, 2)],
+        reference: signal_comparator,
+    },
+    Definition {
+        name: "SIGNAL MULTIPLEXER",
+        segment: 22280,
+        layout: ["EEEE", "EEEE", "XEEE"],
+        inputs: &[("IN.A", 1), ("IN.S", 2), ("IN.B", 3)],
+        outputs: &[("OUT", 2)],
+        sample: &[&[-30, 12, 25, -7, 28], &[-1, 0, 1, 0, -1], &[16, -9, 23, 21, -14]],
+        ranges: &[(-30, 30), (-1, 1), (-30, 30)],
+        reference: signal_multiplexer,
+    },
+    Definition {
+        name: "SEQUENCE GENERATOR",
+        segment: 30647,
+        layout: ["EEEE", "EEEE", "EEEX"],
+        inputs: &[("IN.A", 1), ("IN.B", 2)],
+        outputs: &[("OUT", 2)],
+        sample: &[&[27, 61, 14, 90], &[53, 18, 14, 33]],
+        ranges: &[(10, 99), (10, 99)],
+        reference: sequence_generator,
+    },
+    Definition {
+        name: "SEQUENCE COUNTER",
+        segment: 31904,
+        layout: ["EEEE", "EEEX", "EEEE"],
+        inputs: &[("IN", 1)],
+        outputs: &[("OUT.S", 1), ("OUT.L", 2)],
+        sample: &[&[12, 15, 0, 0, 18, 7, 20, 0]],
+        ranges: &[(0, 20)],
+        reference: sequence_counter,
+    },
+    Definition {
+        name: "SIGNAL EDGE DETECTOR",
+        segment: 32050,
+        layout: ["EEEE", "EEEE", "EXEE"],
+        inputs: &[("IN", 1)],
+        outputs: &[("OUT", 2)],
+        sample: &[&[0, 5, 20, 18, 8, 30, 30]],
+        ranges: &[(0, 50)],
+        reference: signal_edge_detector,
+    },
+    Definition {
+        name: "INTERRUPT HANDLER",
+        segment: 33762,
+        layout: ["EEEE", "EEEE", "XEEE"],
+        inputs: &[("IN.1", 0), ("IN.2", 1), ("IN.3", 2), ("IN.4", 3)],
+        outputs: &[("OUT", 2)],
+        sample: &[&[0, 1, 1, 0, 0], &[0, 0, 0, 1, 1], &[0, 0, 1, 1, 0], &[0, 0, 0, 0, 1]],
+        ranges: &[(0, 1), (0, 1), (0, 1), (0, 1)],
+        reference: interrupt_handler,
+    },
+    Definition {
+        name: "SIGNAL PATTERN DETECTOR",
+        segment: 40196,
+        layout: ["EEEE", "EEEE", "EEEX"],
+        inputs: &[("IN", 1)],
+        outputs: &[("OUT", 2)],
+        sample: &[&[0, 0, 0, 0, 2, 0, 0, 0, 1]],
+        ranges: &[(0, 2)],
+        reference: signal_pattern_detector,
+    },
+    Definition {
+        name: "SEQUENCE PEAK DETECTOR",
+        segment: 41427,
+        layout: ["EEEE", "EEEE", "EEEE"],
+        inputs: &[("IN", 1)],
+        outputs: &[("OUT.I", 1), ("OUT.A", 2)],
+        sample: &[&[15, 12, 17, 0, 8, 0, 14, 19, 3, 0]],
+        ranges: &[(0, 20)],
+        reference: sequence_peak_detector,
+    },
+    Definition {
+        name: "SEQUENCE REVERSER",
+        segment: 42656,
+        layout: ["EEEE", "ESEE", "EEES"],
+        inputs: &[("IN", 1)],
+        outputs: &[("OUT", 2)],
+        sample: &[&[14, 6, 17, 0, 0, 11, 5, 0]],
+        ranges: &[(0, 20)],
+        reference: sequence_reverser,
+    },
+    Definition {
+        name: "SIGNAL MULTIPLIER",
+        segment: 43786,
+        layout: ["EEEE", "SEES", "EEEE"],
+        inputs: &[("IN.A", 1), ("IN.B", 2)],
+        outputs: &[("OUT", 2)],
+        sample: &[&[3, 0, 7, 9, 1], &[8, 6, 5, 0, 9]],
+        ranges: &[(0, 9), (0, 9)],
+        reference: signal_multiplier,
+    },
+];
+
+/// The `Definition` of the campaign puzzle called `name`. Case is ignored and
+/// underscores stand for spaces or dashes, e.g. `SIGNAL_AMPLIFIER`.
+pub fn find(name: &str) -> Option<&'static Definition> {
+    let wanted = normalize(name);
+    CAMPAIGN.iter().find(|definition| normalize(definition.name) == wanted)
+}
+
+fn normalize(name: &str) -> String {
+    name.chars()
+        .map(|character| if character.is_alphanumeric() { character.to_ascii_uppercase() } else { '_' })
+        .collect()
+}
+
+fn self_test_diagnostic(inputs: &[Vec<i32>]) -> Vec<Vec<i32>> {
+    vec![inputs[0].clone(), inputs[1].clone()]
+}
+
+fn signal_amplifier(inputs: &[Vec<i32>]) -> Vec<Vec<i32>> {
+    vec![inputs[0].iter().map(|value| 2 * value).collect()]
+}
+
+fn differential_converter(inputs: &[Vec<i32>]) -> Vec<Vec<i32>> {
+    let pairs: Vec<(i32, i32)> = inputs[0].iter().cloned().zip(inputs[1].iter().cloned()).collect();
+    vec![
+        pairs.iter().map(|&(a, b)| a - b).collect(),
+        pairs.iter().map(|&(a, b)| b - a).collect(),
+    ]
+}
+
+fn signal_comparator(inputs: &[Vec<i32>]) -> Vec<Vec<i32>> {
+    vec![
+        inputs[0].iter().map(|&value| (value > 0) as i32).collect(),
+        inputs[0].iter().map(|&value| (value == 0) as i32).collect(),
+        inputs[0].iter().map(|&value| (value < 0) as i32).collect(),
+    ]
+}
+
+fn signal_multiplexer(inputs: &[Vec<i32>]) -> Vec<Vec<i32>> {
+    vec![inputs[0].iter()
+         .zip(inputs[1].iter())
+         .zip(inputs[2].iter())
+         .map(|((&a, &s), &b)| match s {
+             s if s < 0 => a,
+             0 => a + b,
+             _ => b,
+         })
+         .collect()]
+}
+
+fn sequence_generator(inputs: &[Vec<i32>]) -> Vec<Vec<i32>> {
+    vec![inputs[0].iter()
+         .zip(inputs[1].iter())
+         .flat_map(|(&a, &b)| vec![a.min(b), a.max(b), 0])
+         .collect()]
+}
+
+fn sequence_counter(inputs: &[Vec<i32>]) -> Vec<Vec<i32>> {
+    let sequences = sequences(&inputs[0]);
+    vec![
+        sequences.iter().map(|sequence| sequence.iter().sum()).collect(),
+        sequences.iter().map(|sequence| sequence.len() as i32).collect(),
+    ]
+}
+
+fn signal_edge_detector(inputs: &[Vec<i32>]) -> Vec<Vec<i32>> {
+    let mut previous = 0;
+    vec![inputs[0].iter()
+         .map(|&value| {
+             let edge = ((value - previous).abs() >= 10) as i32;
+             previous = value;
+             edge
+         })
+         .collect()]
+}
+
+fn interrupt_handler(inputs: &[Vec<i32>]) -> Vec<Vec<i32>> {
+    let mut previous = vec![0; inputs.len()];
+    vec![(0..inputs[0].len())
+         .map(|index| {
+             let mut interrupt = 0;
+             for (line, values) in inputs.iter().enumerate() {
+                 if previous[line] == 0 && values[index] == 1 {
+                     interrupt = line as i32 + 1;
+                 }
+                 previous[line] = values[index];
+             }
+             interrupt
+         })
+         .collect()]
+}
+
+fn signal_pattern_detector(inputs: &[Vec<i32>]) -> Vec<Vec<i32>> {
+    let values = &inputs[0];
+    vec![(0..values.len())
+         .map(|index| (index >= 2 && values[index - 2..=index].iter().all(|&value| value == 0)) as i32)
+         .collect()]
+}
+
+fn sequence_peak_detector(inputs: &[Vec<i32>]) -> Vec<Vec<i32>> {
+    let sequences = sequences(&inputs[0]);
+    vec![
+        sequences.iter().map(|sequence| sequence.iter().cloned().min().unwrap_or(0)).collect(),
+        sequences.iter().map(|sequence| sequence.iter().cloned().max().unwrap_or(0)).collect(),
+    ]
+}
+
+fn sequence_reverser(inputs: &[Vec<i32>]) -> Vec<Vec<i32>> {
+    vec![sequences(&inputs[0]).into_iter()
+         .flat_map(|sequence| sequence.into_iter().rev().chain(Some(0)))
+         .collect()]
+}
+
+fn signal_multiplier(inputs: &[Vec<i32>]) -> Vec<Vec<i32>> {
+    vec![inputs[0].iter().zip(inputs[1].iter()).map(|(&a, &b)| a * b).collect()]
+}
+
+/// The zero-terminated sequences of `values`, without their terminators
+fn sequences(values: &[i32]) -> Vec<Vec<i32>> {
+    let mut sequences: Vec<Vec<i32>> = vec![];
+    let mut current: Vec<i32> = vec![];
+    for &value in values {
+        if value == 0 {
+            sequences.push(current);
+            current = vec![];
+        } else {
+            current.push(value);
+        }
+    }
+    sequences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn puzzles_should_be_found_by_name() {
+        assert_eq!(Some(21340), find("signal comparator").map(|definition| definition.segment));
+        assert_eq!(Some(150), find("SELF_TEST_DIAGNOSTIC").map(|definition| definition.segment));
+        assert!(find("SIGNAL DIVIDER").is_none());
+    }
+
+    #[test]
+    fn puzzles_should_compute_reference_output() {
+        let counter: &Definition = find("SEQUENCE_COUNTER").unwrap();
+        let reverser: &Definition = find("SEQUENCE_REVERSER").unwrap();
+        let interrupts: &Definition = find("INTERRUPT_HANDLER").unwrap();
+
+        assert_eq!(vec![vec![47, 0, 76], vec![2, 0, 3]], counter.expected(&[vec![12, 35, 0, 0, 48, 7, 21, 0]]));
+        assert_eq!(vec![vec![3, 2, 0, 0]], reverser.expected(&[vec![2, 3, 0, 0]]));
+        assert_eq!(vec![vec![1, 0, 2, 1]], interrupts.expected(&[vec![1, 1, 0, 1], vec![0, 0, 1, 1], vec![0; 4], vec![0; 4]]));
+    }
+
+    #[test]
+    fn puzzles_should_generate_test_cases_within_ranges() {
+        for definition in CAMPAIGN {
+            let puzzle: Puzzle = definition.generated(11, 2);
+
+            assert_eq!(3, puzzle.tests.len(), "{}", definition.name);
+            for (values, &(minimum, maximum)) in puzzle.tests[2].inputs.iter().zip(definition.ranges) {
+                assert_eq!(STREAM_LENGTH, values.len(), "{}", definition.name);
+                assert!(values.iter().all(|&value| minimum <= value && value <= maximum), "{}", definition.name);
+            }
+        }
+    }
+
+    #[test]
+    fn puzzles_should_sample_values_within_ranges() {
+        for definition in CAMPAIGN {
+            assert_eq!(definition.sample.len(), definition.ranges.len(), "{}", definition.name);
+            for (values, &(minimum, maximum)) in definition.sample.iter().zip(definition.ranges) {
+                for &value in values.iter() {
+                    assert!(minimum <= value && value <= maximum, "{}: {} not in {}..={}", definition.name, value, minimum, maximum);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn puzzles_should_be_complete_puzzles() {
+        for definition in CAMPAIGN {
+            let puzzle: Puzzle = definition.puzzle();
+
+            assert_eq!(3, puzzle.height(), "{}", definition.name);
+            assert_eq!(4, puzzle.width(), "{}", definition.name);
+            assert_eq!(definition.inputs.len(), puzzle.tests[0].inputs.len(), "{}", definition.name);
+            assert_eq!(definition.outputs.len(), puzzle.tests[0].outputs.len(), "{}", definition.name);
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! The `save` module reads and writes the save files of the game.
+//!
+//! A save file has a section for every execution node of a puzzle. Each section
+//! starts with a header `@N`, where `N` counts the execution nodes row by row,
+//! followed by the assembly text of that node. A save file that is read and
+//! written again stays the same byte for byte.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate tis_100_superoptimizer;
+//!
+//! use tis_100_superoptimizer::save::Save;
+//!
+//! fn main() {
+//!     let text = "@0\nMOV UP, DOWN\n\n@1\n\n\n";
+//!     let save: Save = Save::parse(text).unwrap();
+//!
+//!     assert_eq!(2, save.sections.len());
+//!     assert_eq!(text, format!("{}", save));
+//! }
+//! ```
+
+use std::fmt::{Display, Formatter, Error};
+use super::TIS_100::Program;
+use super::TIS_100::Grids::Grid;
+use super::assembly::{parse, ParseError};
+
+/// A `Section` holds the assembly text of a single execution node
+#[derive(Debug,PartialEq,Eq,Clone)]
+pub struct Section {
+    /// The number of the execution node, counted row by row
+    pub index: usize,
+    /// Everything after the `@N` header up to the next header, including the
+    /// line ending of the header itself
+    pub source: String,
+}
+
+impl Section {
+    /// The `Program` of this `Section`. Empty sections have an empty program
+    pub fn program(&self) -> Result<Program, ParseError> {
+        parse(&self.source)
+    }
+}
+
+/// A `Save` is the contents of a save file
+#[derive(Debug,PartialEq,Eq,Clone)]
+pub struct Save {
+    /// The sections of the save file in the order they appear
+    pub sections: Vec<Section>,
+}
+
+impl Display for Save {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        for section in &self.sections {
+            write!(f, "@{}{}", section.index, section.source)?;
+        }
+        Ok(())
+    }
+}
+
+impl Save {
+    /// Parse the text of a save file
+    pub fn parse(text: &str) -> Result<Save, ParseError> {
+        let mut sections: Vec<Section> = vec![];
+        for (line_index, line) in text.split_inclusive('\n').enumerate() {
+            if let Some(header) = line.strip_prefix('@') {
+                let digits: String = header.chars().take_while(|character| character.is_ascii_digit()).collect();
+                let index = digits.parse::<usize>().map_err(|_| ParseError {
+                    line: line_index + 1,
+                    column: 2,
+                    message: String::from("expected a node number after `@`"),
+                })?;
+                sections.push(Section { index, source: header[digits.len()..].to_string() });
+            } else {
+                match sections.last_mut() {
+                    Some(section) => section.source.push_str(line),
+                    None => return Err(ParseError {
+                        line: line_index + 1,
+                        column: 1,
+                        message: String::from("expected a `@N` header"),
+                    }),
+                }
+            }
+        }
+        Ok(Save { sections })
+    }
+
+    /// Create a `Save` with a section for each of `programs`, in the format
+    /// the game writes
+    pub fn from_programs(programs: &[Program]) -> Save {
+        let sections = programs.iter()
+            .enumerate()
+            .map(|(index, program)| Section { index, source: format!("\n{}\n\n", program) })
+            .collect();
+        Save { sections }
+    }
+
+    /// The programs of all sections, indexed by the number of their node.
+    /// Reported errors refer to lines of the whole save file.
+    pub fn programs(&self) -> Result<Vec<Program>, ParseError> {
+        let mut programs: Vec<Program> = vec![];
+        let mut line = 1;
+        for section in &self.sections {
+            let program = section.program().map_err(|error| ParseError { line: line + error.line - 1, .. error })?;
+            if programs.len() <= section.index {
+                programs.resize(section.index + 1, Program(vec![]));
+            }
+            programs[section.index] = program;
+            line += section.source.matches('\n').count();
+        }
+        Ok(programs)
+    }
+
+    /// Load the program of every section into the matching execution node of
+    /// `grid`. Sections without a matching node are ignored.
+    pub fn load_into(&self, grid: &Grid) -> Result<Grid, ParseError> {
+        self.programs().map(|programs| grid.load_programs(&programs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::TIS_100::{Program, Instruction, Source, Destination, Cycle};
+    use super::super::TIS_100::Ports::Direction;
+    use super::super::TIS_100::Grids::Grid;
+    use super::*;
+
+    #[test]
+    fn save_should_be_written_back_byte_for_byte() {
+        let text = "@0\r\nmov up down # echo\r\n\r\n@1\n\n\n@2\nNOP\n";
+
+        let save: Save = Save::parse(text).unwrap();
+
+        assert_eq!(text, format!("{}", save));
+    }
+
+    #[test]
+    fn save_should_keep_empty_nodes() {
+        let save: Save = Save::parse("@0\n\n\n@1\nNEG\n\n").unwrap();
+
+        assert_eq!(Ok(vec![Program(vec![]), Program(vec![Instruction::NEG])]), save.programs());
+    }
+
+    #[test]
+    fn save_should_be_created_from_programs() {
+        let save: Save = Save::from_programs(&[
+            Program(vec![Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::DOWN))]),
+            Program(vec![]),
+        ]);
+
+        assert_eq!("@0\nMOV UP, DOWN\n\n@1\n\n\n", format!("{}", save));
+    }
+
+    #[test]
+    fn save_should_report_errors_at_the_line_in_the_file() {
+        let save: Save = Save::parse("@0\nNOP\n\n@1\nMUL 2\n").unwrap();
+
+        assert_eq!(5, save.programs().unwrap_err().line);
+        assert!(Save::parse("NOP\n@0\n").is_err());
+    }
+
+    #[test]
+    fn save_should_load_programs_into_a_grid() {
+        let save: Save = Save::parse("@0\nMOV UP, RIGHT\n\n@1\nMOV LEFT, DOWN\n\n").unwrap();
+        let grid: Grid = Grid::new(2, 1).with_input(0, vec![1, 2]).with_output(1);
+
+        match save.load_into(&grid).ok().and_then(|loaded| loaded.run(Cycle::Maximum(100)).ok()) {
+            Some(result) => assert_eq!(Some(&vec![1, 2]), result.output(1)),
+            None => panic!(),
+        }
+    }
+}
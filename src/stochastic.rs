@@ -0,0 +1,293 @@
+//! The `stochastic` module searches for programs by random mutation, in the
+//! style of [STOKE](https://theory.stanford.edu/~aiken/publications/papers/asplos13.pdf).
+//!
+//! Exhaustive enumeration stops being feasible around five or six
+//! instructions. A stochastic search instead walks from program to program by
+//! small moves: it replaces, inserts or deletes an instruction, swaps two
+//! instructions or changes one operand of an instruction. The cost of a program is the
+//! distance of its output to the expected output plus its length or its
+//! cycles. A move to a cheaper program is always accepted, a move to a more
+//! expensive program with a probability that falls with the increase in cost,
+//! the Metropolis criterion. The search remembers the best correct program it
+//! came across.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate tis_100_superoptimizer;
+//!
+//! use tis_100_superoptimizer::TIS_100::Node;
+//! use tis_100_superoptimizer::TIS_100::Ports::Port;
+//! use tis_100_superoptimizer::check::check;
+//! use tis_100_superoptimizer::stochastic::{Config, optimize};
+//!
+//! fn main() {
+//!     let node: Node = Node::new().set_up(Port::new(vec![1, 2, 3]));
+//!     let expected_output: Vec<i32> = vec![2, 4, 6];
+//!
+//!     let program = optimize(node.clone(), expected_output.clone(), Config::new(100, 8).with_seed(1)).unwrap();
+//!
+//!     assert!(check(node, program, expected_output, 100));
+//! }
+//! ```
+
+use std::mem::discriminant;
+use super::TIS_100::{Node, Program, Instruction};
+use super::TIS_100::Bytecodes::Bytecode;
+use super::check::{Distance, distance_compiled};
+use super::generator::Random;
+use super::iterator::Alphabet;
+
+/// The default number of moves a search makes
+pub const DEFAULT_ITERATIONS: u64 = 100_000;
+
+/// The default willingness to accept a more expensive program. The cost of
+/// every wrong unit of output is `ERROR_WEIGHT`, so at this temperature the
+/// search still leaves a local minimum now and then.
+pub const DEFAULT_TEMPERATURE: f64 = 10.0;
+
+/// How much the error of the output weighs against the performance of a
+/// program in its cost
+const ERROR_WEIGHT: f64 = 10.0;
+
+/// What the search minimizes among correct programs
+#[derive(Debug,PartialEq,Eq,Clone,Copy)]
+pub enum Objective {
+    /// The number of instructions
+    Length,
+    /// The number of cycles
+    Cycles,
+}
+
+/// Configuration for the stochastic search
+#[derive(Debug,PartialEq,Clone)]
+pub struct Config {
+    /// The maximum allowed number of cycles per program
+    pub maximum_cycle: u32,
+    /// The maximum allowed program length
+    pub maximum_program_length: usize,
+    /// The number of moves the search makes
+    pub iterations: u64,
+    /// The seed of the random numbers, the same seed gives the same search
+    pub seed: u64,
+    /// How willing the search is to accept a more expensive program, must be
+    /// greater than zero
+    pub temperature: f64,
+    /// What the search minimizes
+    pub objective: Objective,
+    /// The instructions programs are built from
+    pub alphabet: Alphabet,
+}
+
+impl Config {
+    /// create a `Config` with prescribed maximum_cycle and maximum_program_length
+    /// that makes `DEFAULT_ITERATIONS` moves at `DEFAULT_TEMPERATURE` to minimize
+    /// the program length
+    pub fn new(maximum_cycle: u32, maximum_program_length: usize) -> Config {
+        Config {
+            maximum_cycle,
+            maximum_program_length,
+            iterations: DEFAULT_ITERATIONS,
+            seed: 0,
+            temperature: DEFAULT_TEMPERATURE,
+            objective: Objective::Length,
+            alphabet: Alphabet::new(),
+        }
+    }
+
+    /// Create a `Config` from self that makes `iterations` moves
+    pub fn with_iterations(&self, iterations: u64) -> Config {
+        Config { iterations, .. self.clone() }
+    }
+
+    /// Create a `Config` from self that draws its random numbers from `seed`
+    pub fn with_seed(&self, seed: u64) -> Config {
+        Config { seed, .. self.clone() }
+    }
+
+    /// Create a `Config` from self with `temperature`, which must be greater
+    /// than zero. At a lower temperature every more expensive program would be
+    /// accepted.
+    pub fn with_temperature(&self, temperature: f64) -> Config {
+        Config { temperature, .. self.clone() }
+    }
+
+    /// Create a `Config` from self that minimizes `objective`
+    pub fn with_objective(&self, objective: Objective) -> Config {
+        Config { objective, .. self.clone() }
+    }
+
+    /// Create a `Config` from self that builds programs from `alphabet`
+    pub fn with_alphabet(&self, alphabet: &Alphabet) -> Config {
+        Config { alphabet: alphabet.clone(), .. self.clone() }
+    }
+}
+
+/// The ways the search moves from one program to the next
+#[derive(Debug,PartialEq,Eq,Clone,Copy)]
+enum Move {
+    Replace,
+    Insert,
+    Delete,
+    Swap,
+    Operand,
+}
+
+const MOVES: [Move; 5] = [Move::Replace, Move::Insert, Move::Delete, Move::Swap, Move::Operand];
+
+/// Searches for a `Program` that satisfies the context by random mutation.
+/// Returns the best correct program the search came across, if any. Panics
+/// when the temperature of `config` is not greater than zero.
+pub fn optimize(node: Node, expected_output: Vec<i32>, config: Config) -> Option<Program> {
+    assert!(config.temperature > 0.0, "temperature must be greater than zero, got {}", config.temperature);
+    let alphabet: Vec<Instruction> = config.alphabet.instructions();
+    if alphabet.is_empty() || config.maximum_program_length == 0 {
+        return None;
+    }
+    let evaluate = |instructions: &[Instruction]| -> (f64, Option<f64>) {
+        let program = Program(instructions.to_vec());
+        let distance = distance_compiled(&node, &Bytecode::compile(&program), &expected_output, config.maximum_cycle);
+        let performance = performance(&config, instructions, &distance);
+        let correct = if distance.error == 0 { Some(performance) } else { None };
+        (ERROR_WEIGHT * distance.error as f64 + performance, correct)
+    };
+
+    let mut random = Random::new(config.seed);
+    let mut current: Vec<Instruction> = vec![alphabet[random.below(alphabet.len())].clone()];
+    let (mut current_cost, correct) = evaluate(&current);
+    let mut best: Option<(f64, Vec<Instruction>)> = correct.map(|performance| (performance, current.clone()));
+
+    for _ in 0..config.iterations {
+        let candidate = mutate(&current, &alphabet, config.maximum_program_length, &mut random);
+        let (cost, correct) = evaluate(&candidate);
+        if let Some(performance) = correct {
+            if best.as_ref().is_none_or(|&(best_performance, _)| performance < best_performance) {
+                best = Some((performance, candidate.clone()));
+            }
+        }
+        if cost <= current_cost || random.fraction() < ((current_cost - cost) / config.temperature).exp() {
+            current = candidate;
+            current_cost = cost;
+        }
+    }
+    best.map(|(_, instructions)| Program(instructions))
+}
+
+fn performance(config: &Config, instructions: &[Instruction], distance: &Distance) -> f64 {
+    match config.objective {
+        Objective::Length => instructions.len() as f64,
+        Objective::Cycles => distance.cycles as f64,
+    }
+}
+
+/// A random move away from `instructions`. Moves that do not apply, like
+/// deleting the only instruction, replace an instruction instead.
+fn mutate(instructions: &[Instruction], alphabet: &[Instruction], maximum_program_length: usize, random: &mut Random) -> Vec<Instruction> {
+    let mut candidate: Vec<Instruction> = instructions.to_vec();
+    let length = candidate.len();
+    let index = random.below(length);
+    let applicable = match MOVES[random.below(MOVES.len())] {
+        Move::Replace => None,
+        Move::Insert if length < maximum_program_length => Some(Move::Insert),
+        Move::Delete if length > 1 => Some(Move::Delete),
+        Move::Swap if length > 1 => Some(Move::Swap),
+        Move::Operand => Some(Move::Operand),
+        _ => None,
+    };
+    match applicable.unwrap_or(Move::Replace) {
+        Move::Replace => candidate[index] = alphabet[random.below(alphabet.len())].clone(),
+        Move::Insert => candidate.insert(random.below(length + 1), alphabet[random.below(alphabet.len())].clone()),
+        Move::Delete => {
+            candidate.remove(index);
+        },
+        Move::Swap => candidate.swap(index, random.below(length)),
+        Move::Operand => candidate[index] = change_operand(&candidate[index], alphabet, random),
+    }
+    candidate
+}
+
+/// `instruction` with one of its operands replaced by a random one from
+/// `alphabet`. A `MOV` keeps either its source or its destination.
+fn change_operand(instruction: &Instruction, alphabet: &[Instruction], random: &mut Random) -> Instruction {
+    let related: Vec<&Instruction> = alphabet.iter()
+        .filter(|&candidate| match (instruction, candidate) {
+            (Instruction::MOV(source, destination), Instruction::MOV(other_source, other_destination)) =>
+                source == other_source || destination == other_destination,
+            _ => discriminant(instruction) == discriminant(candidate),
+        })
+        .collect();
+    if related.is_empty() {
+        instruction.clone()
+    } else {
+        related[random.below(related.len())].clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::TIS_100::{Node, Program, Source, Destination};
+    use super::super::TIS_100::Ports::{Port, Direction};
+    use super::super::check::check;
+    use super::*;
+
+    fn length(program: &Program) -> usize {
+        let Program(ref instructions) = *program;
+        instructions.len()
+    }
+
+    #[test]
+    fn should_find_a_program_of_five_instructions() {
+        let node: Node = Node::new().set_up(Port::new(vec![1, 2, 3]));
+        let expected_output: Vec<i32> = vec![8, 16, 24];
+
+        let program = optimize(node.clone(), expected_output.clone(), Config::new(100, 8).with_seed(1)).unwrap();
+
+        assert!(check(node, program.clone(), expected_output, 100));
+        assert_eq!(5, length(&program));
+    }
+
+    #[test]
+    fn should_be_deterministic_for_a_seed() {
+        let node: Node = Node::new().set_up(Port::new(vec![3, 1, 2]));
+        let expected_output: Vec<i32> = vec![4, 2, 3];
+        let config: Config = Config::new(50, 6).with_iterations(2_000);
+
+        let found = optimize(node.clone(), expected_output.clone(), config.with_seed(2));
+
+        assert!(found.is_some());
+        assert_eq!(found, optimize(node, expected_output, config.with_seed(2)));
+    }
+
+    #[test]
+    #[should_panic(expected = "temperature must be greater than zero")]
+    fn should_reject_a_temperature_of_zero_or_below() {
+        optimize(Node::new(), vec![], Config::new(50, 4).with_temperature(-1.0));
+    }
+
+    #[test]
+    fn should_change_one_operand_of_a_mov() {
+        let alphabet: Vec<Instruction> = Alphabet::new().instructions();
+        let instruction: Instruction = Instruction::MOV(Source::Port(Direction::UP), Destination::Port(Direction::DOWN));
+        let mut random = Random::new(7);
+
+        for _ in 0..100 {
+            match change_operand(&instruction, &alphabet, &mut random) {
+                Instruction::MOV(source, destination) => assert!(
+                    source == Source::Port(Direction::UP) || destination == Destination::Port(Direction::DOWN)),
+                other => panic!("{} is not a MOV", other),
+            }
+        }
+    }
+
+    #[test]
+    fn should_minimize_cycles() {
+        let node: Node = Node::new().set_up(Port::new(vec![3, 1, 2]));
+        let expected_output: Vec<i32> = vec![3, 1, 2];
+        let config: Config = Config::new(50, 4).with_iterations(5_000).with_objective(Objective::Cycles).with_seed(1);
+
+        let program = optimize(node.clone(), expected_output.clone(), config).unwrap();
+
+        assert!(check(node, program.clone(), expected_output, 50));
+        assert_eq!(1, length(&program));
+    }
+}
@@ -19,7 +19,7 @@ output:
     let docs = YamlLoader::load_from_str(s).unwrap();
     let doc = &docs[0];
 
-    assert_eq!(3 as i64, doc["input"][3].as_i64().unwrap());
+    assert_eq!(3_i64, doc["input"][3].as_i64().unwrap());
 }
 
 #[test]
@@ -31,5 +31,5 @@ fn should_read_yaml_from_file() {
     let docs = YamlLoader::load_from_str(&data[..]).unwrap();
     let doc = &docs[0];
 
-    assert_eq!(3 as i64, doc["input"][3].as_i64().unwrap());
+    assert_eq!(3_i64, doc["input"][3].as_i64().unwrap());
 }